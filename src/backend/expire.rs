@@ -0,0 +1,355 @@
+use super::Backend;
+use bytes::Bytes;
+use dashmap::mapref::entry::Entry as MapEntry;
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::debug;
+
+/// how often the active expire cycle runs, redis runs it `hz` times a second
+pub const ACTIVE_EXPIRE_INTERVAL: Duration = Duration::from_millis(100);
+/// the share of the interval one cycle may spend
+const ACTIVE_EXPIRE_BUDGET: Duration = Duration::from_millis(25);
+/// keys sampled per round of the cycle
+const ACTIVE_EXPIRE_KEYS_PER_LOOP: usize = 20;
+/// a round reclaiming more than this percentage of its sample is followed by another one
+const ACTIVE_EXPIRE_ACCEPTABLE_STALE: usize = 10;
+
+/// the NX, XX, GT and LT options of the EXPIRE family
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExpireOptions {
+    /// only if the key has no expiry
+    pub nx: bool,
+    /// only if the key has an expiry
+    pub xx: bool,
+    /// only if the new expiry is later, a key without one never expires so it never is
+    pub gt: bool,
+    /// only if the new expiry is sooner, always the case for a key without one
+    pub lt: bool,
+}
+
+/// when a key expires
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiry {
+    /// there is no such key
+    Missing,
+    /// the key never expires
+    Never,
+    /// unix time in milliseconds
+    At(i64),
+}
+
+/// what a write does to the expiry of the key
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExpiryUpdate {
+    /// the key no longer expires
+    #[default]
+    Clear,
+    /// the key keeps the expiry it had
+    Keep,
+    /// unix time in milliseconds
+    At(i64),
+}
+
+/// the keys that may have an expiry, kept in a vec so the active cycle can sample them. Entries
+/// go stale when a key is removed, overwritten or persisted, the cycle drops them as it finds
+/// them.
+#[derive(Debug)]
+pub(crate) struct VolatileKeys {
+    keys: Vec<Bytes>,
+    index: HashMap<Bytes, usize>,
+    /// xorshift state picking the sampled keys
+    seed: u64,
+}
+
+impl ExpireOptions {
+    fn allows(&self, current: Option<i64>, at: i64) -> bool {
+        !(self.nx && current.is_some()
+            || self.xx && current.is_none()
+            || self.gt && current.is_none_or(|c| at <= c)
+            || self.lt && current.is_some_and(|c| at >= c))
+    }
+}
+
+impl Default for VolatileKeys {
+    fn default() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or_default();
+        Self {
+            keys: Vec::new(),
+            index: HashMap::new(),
+            seed: u64::from(nanos) | 1,
+        }
+    }
+}
+
+impl VolatileKeys {
+    pub(super) fn insert(&mut self, key: Bytes) {
+        if !self.index.contains_key(&key) {
+            self.index.insert(key.clone(), self.keys.len());
+            self.keys.push(key);
+        }
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        if let Some(i) = self.index.remove(key) {
+            self.keys.swap_remove(i);
+            if let Some(moved) = self.keys.get(i) {
+                self.index.insert(moved.clone(), i);
+            }
+        }
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// up to `n` distinct keys, each picked at random on its own. Keys given an expiry together
+    /// sit next to each other, a run of them would skew the share of stale keys.
+    fn sample(&mut self, n: usize) -> Vec<Bytes> {
+        if n >= self.keys.len() {
+            return self.keys.clone();
+        }
+        let mut picked = (0..n)
+            .map(|_| (self.next_random() % self.keys.len() as u64) as usize)
+            .collect::<Vec<_>>();
+        picked.sort_unstable();
+        picked.dedup();
+        picked.into_iter().map(|i| self.keys[i].clone()).collect()
+    }
+
+    fn next_random(&mut self) -> u64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        self.seed
+    }
+}
+
+/// the current unix time in milliseconds
+pub fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+impl Backend {
+    /// set the key to expire at the unix time in milliseconds, a time already passed deletes it.
+    /// Returns whether the key exists and the options allowed it.
+    pub fn expire_at(&self, key: &[u8], at: i64, options: ExpireOptions) -> bool {
+        let now = now_ms();
+        let Some(mut entry) = self.keyspace.get_mut(key) else {
+            return false;
+        };
+        if entry.is_expired(now) || !options.allows(entry.expires_at, at) {
+            return false;
+        }
+        if at <= now {
+            drop(entry);
+            self.keyspace.remove(key);
+            return true;
+        }
+        entry.expires_at = Some(at);
+        self.track_expiry(entry.key());
+        true
+    }
+
+    pub fn expiry(&self, key: &[u8]) -> Expiry {
+        match self.lookup(key) {
+            Some(entry) => entry.expires_at.map_or(Expiry::Never, Expiry::At),
+            None => Expiry::Missing,
+        }
+    }
+
+    /// remove the expiry of the key, returns whether it had one
+    pub fn persist(&self, key: &[u8]) -> bool {
+        let now = now_ms();
+        match self.keyspace.get_mut(key) {
+            Some(mut entry) if !entry.is_expired(now) => entry.expires_at.take().is_some(),
+            _ => false,
+        }
+    }
+
+    /// reclaim expired keys in the background until the server shuts down
+    pub async fn run_active_expire(&self) {
+        let mut interval = tokio::time::interval(ACTIVE_EXPIRE_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = self.shutting_down() => return,
+            }
+            let reclaimed = self.active_expire_cycle(ACTIVE_EXPIRE_BUDGET);
+            if reclaimed > 0 {
+                debug!("Active expire reclaimed {} keys", reclaimed);
+            }
+        }
+    }
+
+    /// sample keys with an expiry and remove the expired ones, like redis does: another round
+    /// follows as long as many of the sampled keys had expired and the budget isn't spent.
+    /// Returns the number of keys removed.
+    pub fn active_expire_cycle(&self, budget: Duration) -> usize {
+        let start = Instant::now();
+        let mut reclaimed = 0;
+        loop {
+            let sample = self.volatile().sample(ACTIVE_EXPIRE_KEYS_PER_LOOP);
+            if sample.is_empty() {
+                break;
+            }
+            let now = now_ms();
+            let mut stale = 0;
+            for key in &sample {
+                // decided under the key's lock, an EXPIRE racing with us can't be lost
+                match self.keyspace.entry(key.clone()) {
+                    MapEntry::Occupied(entry) if entry.get().is_expired(now) => {
+                        self.volatile().remove(key);
+                        entry.remove();
+                        reclaimed += 1;
+                        stale += 1;
+                    }
+                    MapEntry::Occupied(entry) if entry.get().expires_at.is_some() => {}
+                    _ => {
+                        self.volatile().remove(key);
+                        stale += 1;
+                    }
+                }
+            }
+            if stale * 100 <= sample.len() * ACTIVE_EXPIRE_ACCEPTABLE_STALE
+                || start.elapsed() >= budget
+            {
+                break;
+            }
+        }
+        reclaimed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{backend::Entry, Value};
+
+    fn options(nx: bool, xx: bool, gt: bool, lt: bool) -> ExpireOptions {
+        ExpireOptions { nx, xx, gt, lt }
+    }
+
+    #[test]
+    fn test_expire_options() {
+        let backend = Backend::new();
+        let later = now_ms() + 100_000;
+        backend.set("k".into(), "v".into());
+
+        assert!(!backend.expire_at(b"k", later, options(false, true, false, false)));
+        assert!(!backend.expire_at(b"k", later, options(false, false, true, false)));
+        assert!(backend.expire_at(b"k", later, options(true, false, false, false)));
+        assert!(!backend.expire_at(b"k", later, options(true, false, false, false)));
+        assert_eq!(backend.expiry(b"k"), Expiry::At(later));
+
+        assert!(!backend.expire_at(b"k", later + 1, options(false, false, false, true)));
+        assert!(backend.expire_at(b"k", later + 1, options(false, true, true, false)));
+        assert!(backend.expire_at(b"k", later, options(false, false, false, true)));
+        assert_eq!(backend.expiry(b"k"), Expiry::At(later));
+
+        assert!(backend.persist(b"k"));
+        assert!(!backend.persist(b"k"));
+        assert_eq!(backend.expiry(b"k"), Expiry::Never);
+        assert!(backend.expire_at(b"k", later, options(false, false, false, true)));
+
+        assert!(!backend.expire_at(b"missing", later, ExpireOptions::default()));
+        assert_eq!(backend.expiry(b"missing"), Expiry::Missing);
+    }
+
+    #[test]
+    fn test_lazy_expire() {
+        let backend = Backend::new();
+        backend.set("k".into(), "v".into());
+        backend.hset("h".into(), "f".into(), "v".into()).unwrap();
+        // a deadline in the past deletes right away
+        assert!(backend.expire_at(b"k", now_ms() - 1, ExpireOptions::default()));
+        assert_eq!(backend.get(b"k"), Ok(None));
+
+        // expired but not reclaimed yet, reads don't see it
+        backend.keyspace.get_mut(b"h".as_ref()).unwrap().expires_at = Some(now_ms() - 10);
+        assert_eq!(backend.key_type(b"h"), None);
+        assert!(!backend.keyspace.contains_key(b"h".as_ref()));
+
+        backend.keyspace.insert(
+            "h".into(),
+            Entry {
+                value: Value::String("v".into()),
+                expires_at: Some(now_ms() - 10),
+            },
+        );
+        // a write starts from an empty key
+        assert_eq!(backend.hset("h".into(), "f".into(), "v".into()), Ok(true));
+        assert_eq!(backend.expiry(b"h"), Expiry::Never);
+    }
+
+    #[test]
+    fn test_set_clears_expiry() {
+        let backend = Backend::new();
+        backend.set("k".into(), "v".into());
+        assert!(backend.expire_at(b"k", now_ms() + 10_000, ExpireOptions::default()));
+        backend.set("k".into(), "w".into());
+        assert_eq!(backend.expiry(b"k"), Expiry::Never);
+    }
+
+    #[test]
+    fn test_active_expire_cycle() {
+        let backend = Backend::new();
+        let now = now_ms();
+        for i in 0..1000 {
+            let key = Bytes::from(format!("key:{}", i));
+            backend.set(key.clone(), "v".into());
+            assert!(backend.expire_at(&key, now + 100_000, ExpireOptions::default()));
+            // most keys expire, a few stay
+            if i % 10 != 0 {
+                backend.keyspace.get_mut(&key).unwrap().expires_at = Some(now - 10);
+            }
+        }
+        backend.set("persistent".into(), "v".into());
+        backend.set("key:0".into(), "v".into());
+
+        let mut reclaimed = 0;
+        while backend.volatile().len() > 99 {
+            reclaimed += backend.active_expire_cycle(Duration::from_secs(1));
+        }
+        assert_eq!(reclaimed, 900);
+        assert_eq!(backend.keyspace.len(), 101);
+        assert_eq!(backend.expiry(b"key:10"), Expiry::At(now + 100_000));
+        assert_eq!(backend.expiry(b"key:0"), Expiry::Never);
+    }
+
+    #[test]
+    fn test_volatile_keys() {
+        let mut keys = VolatileKeys::default();
+        for key in ["a", "b", "c", "a"] {
+            keys.insert(key.into());
+        }
+        assert_eq!(keys.len(), 3);
+        keys.remove(b"a");
+        keys.remove(b"x");
+        assert_eq!(keys.len(), 2);
+        let mut sample = keys.sample(5);
+        sample.sort();
+        assert_eq!(sample, vec![Bytes::from("b"), Bytes::from("c")]);
+
+        // keys are picked on their own, not as a run from a random start
+        let mut keys = VolatileKeys::default();
+        for i in 0..1000 {
+            keys.insert(Bytes::from(i.to_string()));
+        }
+        for _ in 0..10 {
+            let sample = keys.sample(20);
+            let mut picked = sample.iter().map(|k| keys.index[k]).collect::<Vec<_>>();
+            picked.sort();
+            picked.dedup();
+            assert_eq!(picked.len(), sample.len());
+            assert!(picked.len() > 10);
+            assert!(picked[picked.len() - 1] - picked[0] >= picked.len());
+        }
+    }
+}
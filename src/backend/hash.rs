@@ -0,0 +1,36 @@
+use super::{detach, now_ms, Backend, Entry, Value, WrongType};
+use bytes::Bytes;
+
+impl Backend {
+    pub fn hget(&self, key: &[u8], field: &[u8]) -> Result<Option<Bytes>, WrongType> {
+        match self.lookup(key).as_deref().map(|e| &e.value) {
+            Some(Value::Hash(hash)) => Ok(hash.get(field).cloned()),
+            Some(_) => Err(WrongType),
+            None => Ok(None),
+        }
+    }
+
+    /// returns whether the field is new
+    pub fn hset(&self, key: Bytes, field: Bytes, value: Bytes) -> Result<bool, WrongType> {
+        let empty = || Entry::new(Value::Hash(Default::default()));
+        let mut entry = self.keyspace.entry(detach(key)).or_insert_with(empty);
+        if entry.is_expired(now_ms()) {
+            *entry = empty();
+        }
+        match &mut entry.value {
+            Value::Hash(hash) => Ok(hash.insert(detach(field), detach(value)).is_none()),
+            _ => Err(WrongType),
+        }
+    }
+
+    /// the fields and values of a hash, empty if there is no such key
+    pub fn hgetall(&self, key: &[u8]) -> Result<Vec<(Bytes, Bytes)>, WrongType> {
+        match self.lookup(key).as_deref().map(|e| &e.value) {
+            Some(Value::Hash(hash)) => {
+                Ok(hash.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            }
+            Some(_) => Err(WrongType),
+            None => Ok(Vec::new()),
+        }
+    }
+}
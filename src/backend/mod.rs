@@ -1,15 +1,92 @@
-use crate::RespFrame;
-use dashmap::DashMap;
+mod expire;
+mod hash;
+mod string;
+mod value;
+
+use crate::{Client, RespLimits};
+use anyhow::Result;
+use bytes::Bytes;
+use dashmap::{mapref::one::Ref, DashMap};
+use expire::VolatileKeys;
 use std::ops::Deref;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard};
+use thiserror::Error;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+pub use expire::{now_ms, ExpireOptions, Expiry, ExpiryUpdate, ACTIVE_EXPIRE_INTERVAL};
+pub use string::{SetCondition, SetOptions, StringError};
+pub(crate) use value::{parse_float, parse_int};
+pub use value::{Stream, StreamId, Value, ZSet};
 
 #[derive(Debug, Clone)]
 pub struct Backend(Arc<BackendInner>);
 
 #[derive(Debug)]
 pub struct BackendInner {
-    pub(crate) map: DashMap<String, RespFrame>,
-    pub(crate) hmap: DashMap<String, DashMap<String, RespFrame>>,
+    /// every key with its typed value. Keys and hash fields are raw bytes, like in redis they
+    /// don't have to be UTF-8. Only big values share the request buffer they were read from, see
+    /// `detach`.
+    pub(crate) keyspace: DashMap<Bytes, Entry>,
+    /// keys that may have an expiry, for the active expire cycle to sample
+    volatile: Mutex<VolatileKeys>,
+    /// connected clients by id
+    pub(crate) clients: DashMap<u64, Arc<Client>>,
+    /// cancelled once the server starts shutting down
+    shutdown: CancellationToken,
+    shutdown_mode: Mutex<ShutdownMode>,
+    /// what clients may send, every connection decodes with them
+    limits: RespLimits,
+}
+
+/// a value with the unix time in milliseconds it expires at
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Entry {
+    pub(crate) value: Value,
+    pub(crate) expires_at: Option<i64>,
+}
+
+impl Entry {
+    pub(crate) fn new(value: Value) -> Self {
+        Self {
+            value,
+            expires_at: None,
+        }
+    }
+
+    /// redis keeps a key until its deadline is strictly in the past
+    pub(crate) fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|at| at < now)
+    }
+}
+
+/// a command hit a key holding another type than the one it works on
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+#[error("Operation against a key holding the wrong kind of value")]
+pub struct WrongType;
+
+/// values from this size on are stored as the slice of the request buffer they were read from,
+/// like redis keeps big bulk arguments as they are in the query buffer
+const SHARED_VALUE_MIN: usize = 32 * 1024;
+
+/// bytes to keep in the keyspace, copied into their own allocation unless they are big. A key or
+/// value sliced from the read buffer would otherwise keep the whole pipelined batch alive.
+fn detach(bytes: Bytes) -> Bytes {
+    if bytes.len() < SHARED_VALUE_MIN {
+        Bytes::copy_from_slice(&bytes)
+    } else {
+        bytes
+    }
+}
+
+/// how the server saves before exiting, set by SHUTDOWN or a signal
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ShutdownMode {
+    /// `Some(true)` for SAVE, `Some(false)` for NOSAVE, `None` saves only if persistence is
+    /// configured
+    pub save: Option<bool>,
+    /// exit even if saving fails
+    pub force: bool,
 }
 
 impl Deref for Backend {
@@ -29,8 +106,12 @@ impl Default for Backend {
 impl Default for BackendInner {
     fn default() -> Self {
         Self {
-            map: DashMap::new(),
-            hmap: DashMap::new(),
+            keyspace: DashMap::new(),
+            volatile: Mutex::new(VolatileKeys::default()),
+            clients: DashMap::new(),
+            shutdown: CancellationToken::new(),
+            shutdown_mode: Mutex::new(ShutdownMode::default()),
+            limits: RespLimits::default(),
         }
     }
 }
@@ -40,26 +121,158 @@ impl Backend {
         Self::default()
     }
 
-    pub fn get(&self, key: &str) -> Option<RespFrame> {
-        self.map.get(key).map(|v| v.value().clone())
+    pub fn with_limits(limits: RespLimits) -> Self {
+        Self(Arc::new(BackendInner {
+            limits,
+            ..BackendInner::default()
+        }))
     }
 
-    pub fn set(&self, key: String, value: RespFrame) {
-        self.map.insert(key, value);
+    pub fn limits(&self) -> &RespLimits {
+        &self.limits
     }
 
-    pub fn hget(&self, key: &str, field: &str) -> Option<RespFrame> {
-        self.hmap
-            .get(key)
-            .and_then(|v| v.get(field).map(|v| v.value().clone()))
+    /// the entry under the key, one past its deadline is removed instead
+    fn lookup(&self, key: &[u8]) -> Option<Ref<'_, Bytes, Entry>> {
+        let now = now_ms();
+        match self.keyspace.get(key) {
+            Some(entry) if entry.is_expired(now) => {
+                drop(entry);
+                self.keyspace
+                    .remove_if(key, |_, entry| entry.is_expired(now));
+                None
+            }
+            entry => entry,
+        }
     }
 
-    pub fn hset(&self, key: String, field: String, value: RespFrame) {
-        let hmap = self.hmap.entry(key).or_default();
-        hmap.insert(field, value);
+    fn volatile(&self) -> MutexGuard<'_, VolatileKeys> {
+        self.volatile.lock().unwrap_or_else(|e| e.into_inner())
     }
 
-    pub fn hgetall(&self, key: &str) -> Option<DashMap<String, RespFrame>> {
-        self.hmap.get(key).map(|v| v.clone())
+    /// note a key that got an expiry, called while the key is locked so the active cycle can't
+    /// drop it as stale in between
+    fn track_expiry(&self, key: &Bytes) {
+        self.volatile().insert(key.clone());
+    }
+
+    /// the type name of the value under the key, `None` if there is no such key
+    pub fn key_type(&self, key: &[u8]) -> Option<&'static str> {
+        self.lookup(key).map(|e| e.value.type_name())
+    }
+
+    /// the encoding of the value under the key, `None` if there is no such key
+    pub fn encoding(&self, key: &[u8]) -> Option<&'static str> {
+        self.lookup(key).map(|e| e.value.encoding())
+    }
+
+    pub fn register_client(&self, client: Arc<Client>) {
+        self.clients.insert(client.id(), client);
+    }
+
+    pub fn unregister_client(&self, id: u64) {
+        self.clients.remove(&id);
+    }
+
+    /// the connected clients ordered by id
+    pub fn clients(&self) -> Vec<Arc<Client>> {
+        let mut clients = self
+            .clients
+            .iter()
+            .map(|v| v.value().clone())
+            .collect::<Vec<_>>();
+        clients.sort_by_key(|c| c.id());
+        clients
+    }
+
+    /// start shutting down: listeners stop accepting and connections close after their current
+    /// batch. Only the first request sets the mode.
+    pub fn shutdown(&self, mode: ShutdownMode) {
+        let mut current = self.shutdown_mode.lock().unwrap_or_else(|e| e.into_inner());
+        if self.shutdown.is_cancelled() {
+            return;
+        }
+        info!("Shutdown requested: {:?}", mode);
+        *current = mode;
+        self.shutdown.cancel();
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown.is_cancelled()
+    }
+
+    /// resolves once a shutdown is requested
+    pub async fn shutting_down(&self) {
+        self.shutdown.cancelled().await
+    }
+
+    /// run once every connection is closed, saves the dataset as the shutdown mode asks
+    pub fn finish_shutdown(&self) -> Result<()> {
+        let mode = *self.shutdown_mode.lock().unwrap_or_else(|e| e.into_inner());
+        // nothing configures persistence yet, so only an explicit SAVE saves
+        if mode.save.unwrap_or(false) {
+            if let Err(e) = self.save() {
+                if !mode.force {
+                    return Err(e);
+                }
+                warn!("Error saving the dataset, exiting anyway: {:?}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// write the dataset to disk. There is no persistence yet, this is the hook it plugs into.
+    pub fn save(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shutdown_keeps_first_mode() -> Result<()> {
+        let backend = Backend::new();
+        assert!(!backend.is_shutting_down());
+
+        let nosave = ShutdownMode {
+            save: Some(false),
+            force: false,
+        };
+        backend.shutdown(nosave);
+        backend.shutdown(ShutdownMode {
+            save: Some(true),
+            force: true,
+        });
+        assert!(backend.is_shutting_down());
+        assert_eq!(*backend.shutdown_mode.lock().unwrap(), nosave);
+        backend.finish_shutdown()
+    }
+
+    #[test]
+    fn test_keyspace_types() {
+        let backend = Backend::new();
+        backend.set("s".into(), "v".into());
+        assert_eq!(backend.hset("h".into(), "f".into(), "v".into()), Ok(true));
+        assert_eq!(backend.hset("h".into(), "f".into(), "w".into()), Ok(false));
+
+        assert_eq!(backend.key_type(b"s"), Some("string"));
+        assert_eq!(backend.key_type(b"h"), Some("hash"));
+        assert_eq!(backend.key_type(b"none"), None);
+
+        assert_eq!(backend.get(b"h"), Err(WrongType));
+        assert_eq!(backend.hget(b"s", b"f"), Err(WrongType));
+        assert_eq!(
+            backend.hset("s".into(), "f".into(), "v".into()),
+            Err(WrongType)
+        );
+        assert_eq!(backend.hgetall(b"s"), Err(WrongType));
+        assert_eq!(backend.hget(b"h", b"f"), Ok(Some("w".into())));
+
+        // SET replaces a value of any type
+        backend.set("h".into(), "1".into());
+        assert_eq!(backend.key_type(b"h"), Some("string"));
+        assert_eq!(backend.encoding(b"h"), Some("int"));
     }
 }
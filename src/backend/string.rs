@@ -0,0 +1,694 @@
+use super::{
+    detach, now_ms, parse_float, parse_int, Backend, Entry, ExpiryUpdate, Value, WrongType,
+};
+use bytes::Bytes;
+use dashmap::mapref::entry::Entry as MapEntry;
+use thiserror::Error;
+
+/// the NX and XX options of SET
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SetCondition {
+    #[default]
+    Always,
+    /// NX, only if the key doesn't exist
+    IfMissing,
+    /// XX, only if the key exists
+    IfExists,
+}
+
+/// the options of SET
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SetOptions {
+    pub condition: SetCondition,
+    pub expiry: ExpiryUpdate,
+    /// GET, reply with the string the key held
+    pub get: bool,
+}
+
+/// why a string command couldn't update the value, the messages are the ones redis replies with
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum StringError {
+    #[error(transparent)]
+    WrongType(#[from] WrongType),
+    #[error("value is not an integer or out of range")]
+    NotAnInteger,
+    #[error("value is not a valid float")]
+    NotAFloat,
+    #[error("increment or decrement would overflow")]
+    Overflow,
+    #[error("increment would produce NaN or Infinity")]
+    NanOrInfinity,
+    #[error("string exceeds maximum allowed size (proto-max-bulk-len)")]
+    TooLarge,
+}
+
+impl Backend {
+    pub fn get(&self, key: &[u8]) -> Result<Option<Bytes>, WrongType> {
+        match self.lookup(key).as_deref().map(|e| &e.value) {
+            Some(Value::String(s)) => Ok(Some(s.clone())),
+            Some(_) => Err(WrongType),
+            None => Ok(None),
+        }
+    }
+
+    /// set a string, whatever the key held before. The key no longer expires.
+    pub fn set(&self, key: Bytes, value: Bytes) {
+        self.keyspace
+            .insert(detach(key), Entry::new(Value::String(detach(value))));
+    }
+
+    /// SET with its options, in one step under the key's lock. Returns whether the value was set
+    /// and, with `get`, the string the key held. Asking for it when the key holds another type is
+    /// an error and sets nothing.
+    pub fn set_with(
+        &self,
+        key: Bytes,
+        value: Bytes,
+        options: SetOptions,
+    ) -> Result<(bool, Option<Bytes>), WrongType> {
+        let entry = self.keyspace.entry(detach(key));
+        let current = match &entry {
+            MapEntry::Occupied(e) if !e.get().is_expired(now_ms()) => Some(e.get()),
+            _ => None,
+        };
+        let old = match current.map(|e| &e.value) {
+            Some(Value::String(s)) if options.get => Some(s.clone()),
+            Some(_) if options.get => return Err(WrongType),
+            _ => None,
+        };
+        let expires_at = match options.expiry {
+            ExpiryUpdate::Clear => None,
+            ExpiryUpdate::Keep => current.and_then(|e| e.expires_at),
+            ExpiryUpdate::At(at) => Some(at),
+        };
+        let allowed = match options.condition {
+            SetCondition::Always => true,
+            SetCondition::IfMissing => current.is_none(),
+            SetCondition::IfExists => current.is_some(),
+        };
+        if !allowed {
+            return Ok((false, old));
+        }
+
+        let entry = entry.insert(Entry {
+            value: Value::String(detach(value)),
+            expires_at,
+        });
+        if expires_at.is_some() {
+            self.track_expiry(entry.key());
+        }
+        Ok((true, old))
+    }
+
+    /// the string under the key, updating its expiry
+    pub fn getex(&self, key: &[u8], expiry: ExpiryUpdate) -> Result<Option<Bytes>, WrongType> {
+        let mut entry = match self.keyspace.get_mut(key) {
+            Some(entry) if !entry.is_expired(now_ms()) => entry,
+            _ => return Ok(None),
+        };
+        let value = match &entry.value {
+            Value::String(s) => s.clone(),
+            _ => return Err(WrongType),
+        };
+        match expiry {
+            ExpiryUpdate::Keep => {}
+            ExpiryUpdate::Clear => entry.expires_at = None,
+            ExpiryUpdate::At(at) => {
+                entry.expires_at = Some(at);
+                self.track_expiry(entry.key());
+            }
+        }
+        Ok(Some(value))
+    }
+
+    /// add to the integer the key holds, a missing key counts as 0. The key keeps its expiry.
+    pub fn incr_by(&self, key: Bytes, delta: i64) -> Result<i64, StringError> {
+        self.update_string(key, |value| {
+            let n = match value {
+                Some(value) => parse_int(value).ok_or(StringError::NotAnInteger)?,
+                None => 0,
+            };
+            let n = n.checked_add(delta).ok_or(StringError::Overflow)?;
+            *value = Some(Bytes::copy_from_slice(
+                itoa::Buffer::new().format(n).as_bytes(),
+            ));
+            Ok(n)
+        })
+    }
+
+    /// add to the float the key holds, a missing key counts as 0. The result is stored the way
+    /// redis prints it, `10.5` plus `0.1` is `10.6`.
+    pub fn incr_by_float(&self, key: Bytes, delta: f64) -> Result<Bytes, StringError> {
+        self.update_string(key, |value| {
+            let current = match value {
+                Some(value) => parse_float(value).ok_or(StringError::NotAFloat)?,
+                None => 0.0,
+            };
+            if !(current + delta).is_finite() {
+                return Err(StringError::NanOrInfinity);
+            }
+            let n = Bytes::from(format_sum(value.as_deref(), current, delta));
+            *value = Some(n.clone());
+            Ok(n)
+        })
+    }
+
+    /// append to the string the key holds, creating it if needed. Returns the new length.
+    pub fn append(&self, key: Bytes, suffix: &[u8]) -> Result<usize, StringError> {
+        let max_len = self.limits.max_bulk_len;
+        self.update_string(key, |value| {
+            let len = value.as_ref().map_or(0, |v| v.len()) + suffix.len();
+            if len > max_len {
+                return Err(StringError::TooLarge);
+            }
+            *value = Some(extend(value.take().unwrap_or_default(), len, |buf| {
+                buf.extend_from_slice(suffix)
+            }));
+            Ok(len)
+        })
+    }
+
+    /// the length of the string the key holds, 0 if there is no such key
+    pub fn strlen(&self, key: &[u8]) -> Result<usize, WrongType> {
+        match self.lookup(key).as_deref().map(|e| &e.value) {
+            Some(Value::String(s)) => Ok(s.len()),
+            Some(_) => Err(WrongType),
+            None => Ok(0),
+        }
+    }
+
+    /// the bytes from `start` to `end` inclusive, negative offsets count from the end. Ranges out
+    /// of the string are clamped like redis does.
+    pub fn getrange(&self, key: &[u8], start: i64, end: i64) -> Result<Bytes, WrongType> {
+        let value = self.get(key)?.unwrap_or_default();
+        let len = value.len() as i64;
+        if start < 0 && end < 0 && start > end {
+            return Ok(Bytes::new());
+        }
+        let start = if start < 0 { len + start } else { start }.max(0);
+        let end = if end < 0 { len + end } else { end }.max(0).min(len - 1);
+        if start > end || len == 0 {
+            return Ok(Bytes::new());
+        }
+        Ok(value.slice(start as usize..=end as usize))
+    }
+
+    /// overwrite the string from `offset` on, padding it with zero bytes up to the offset. An
+    /// empty `data` changes nothing and doesn't create the key. Returns the new length.
+    pub fn setrange(&self, key: Bytes, offset: usize, data: &[u8]) -> Result<usize, StringError> {
+        let max_len = self.limits.max_bulk_len;
+        self.update_string(key, |value| {
+            let current = value.as_ref().map_or(0, |v| v.len());
+            if data.is_empty() {
+                return Ok(current);
+            }
+            let end = offset
+                .checked_add(data.len())
+                .filter(|end| *end <= max_len)
+                .ok_or(StringError::TooLarge)?;
+            let len = current.max(end);
+            *value = Some(extend(value.take().unwrap_or_default(), len, |buf| {
+                if buf.len() < end {
+                    buf.resize(end, 0);
+                }
+                buf[offset..end].copy_from_slice(data);
+            }));
+            Ok(len)
+        })
+    }
+
+    /// edit the string under the key with `f`, it gets `None` for a missing key and the key is
+    /// created if it leaves a value. This runs under the key's lock, so concurrent updates are
+    /// never lost. The key keeps its expiry, and `f` fails before changing anything.
+    fn update_string<T, E: From<WrongType>>(
+        &self,
+        key: Bytes,
+        f: impl FnOnce(&mut Option<Bytes>) -> Result<T, E>,
+    ) -> Result<T, E> {
+        match self.keyspace.entry(detach(key)) {
+            MapEntry::Occupied(mut entry) if !entry.get().is_expired(now_ms()) => {
+                let Value::String(s) = &mut entry.get_mut().value else {
+                    return Err(WrongType.into());
+                };
+                // taken out, so a buffer nobody else shares is edited in place
+                let mut value = Some(std::mem::take(s));
+                let ret = f(&mut value);
+                *s = value.unwrap_or_default();
+                ret
+            }
+            entry => {
+                let mut value = None;
+                let ret = f(&mut value)?;
+                if let Some(value) = value {
+                    entry.insert(Entry::new(Value::String(value)));
+                }
+                Ok(ret)
+            }
+        }
+    }
+
+    /// remove the key if it holds a string, returning it
+    pub fn getdel(&self, key: Bytes) -> Result<Option<Bytes>, WrongType> {
+        let MapEntry::Occupied(entry) = self.keyspace.entry(key) else {
+            return Ok(None);
+        };
+        if entry.get().is_expired(now_ms()) {
+            entry.remove();
+            return Ok(None);
+        }
+        match &entry.get().value {
+            Value::String(s) => {
+                let value = s.clone();
+                entry.remove();
+                Ok(Some(value))
+            }
+            _ => Err(WrongType),
+        }
+    }
+}
+
+/// significant digits INCRBYFLOAT keeps, as redis does
+const FLOAT_DIGITS: u32 = 17;
+
+/// the sum INCRBYFLOAT stores. Redis adds in long double, close enough to adding the decimals
+/// the operands are written as, so that is what we do: `0.2` plus `0.1` is `0.3`, not
+/// `0.30000000000000004`. Operands too far apart to add exactly fall back to the f64 sum.
+fn format_sum(current: Option<&[u8]>, current_float: f64, delta: f64) -> String {
+    // `{:e}` is the shortest form that reads back as the same float
+    let decimal = |f: f64| parse_decimal(format!("{:e}", f).as_bytes());
+    let current = match current {
+        Some(s) => parse_decimal(s).or_else(|| decimal(current_float)),
+        None => Some((0, 0)),
+    };
+    match current
+        .zip(decimal(delta))
+        .and_then(|(a, b)| add_decimal(a, b))
+    {
+        Some((mantissa, exp)) => format_decimal(mantissa, exp),
+        None => (current_float + delta).to_string(),
+    }
+}
+
+/// a decimal like `-1.5e3` as `mantissa * 10^exp`, `None` if it doesn't fit
+fn parse_decimal(s: &[u8]) -> Option<(i128, i32)> {
+    let s = std::str::from_utf8(s).ok()?;
+    let (s, exp) = match s.split_once(['e', 'E']) {
+        Some((s, exp)) => (s, exp.parse::<i32>().ok()?),
+        None => (s, 0),
+    };
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+    if int.is_empty() && frac.is_empty() {
+        return None;
+    }
+    let mut mantissa = 0i128;
+    for b in int.bytes().chain(frac.bytes()) {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        mantissa = mantissa
+            .checked_mul(10)?
+            .checked_add(i128::from(b - b'0'))?;
+    }
+    let exp = exp.checked_sub(i32::try_from(frac.len()).ok()?)?;
+    Some((if negative { -mantissa } else { mantissa }, exp))
+}
+
+fn add_decimal((a, a_exp): (i128, i32), (b, b_exp): (i128, i32)) -> Option<(i128, i32)> {
+    if a == 0 || b == 0 {
+        return Some(if a == 0 { (b, b_exp) } else { (a, a_exp) });
+    }
+    let exp = a_exp.min(b_exp);
+    let scale = |m: i128, e: i32| {
+        let shift = u32::try_from(e.checked_sub(exp)?).ok()?;
+        m.checked_mul(10i128.checked_pow(shift)?)
+    };
+    Some((scale(a, a_exp)?.checked_add(scale(b, b_exp)?)?, exp))
+}
+
+/// `mantissa * 10^exp` rounded to `FLOAT_DIGITS` significant digits, without an exponent or
+/// trailing zeros
+fn format_decimal(mantissa: i128, mut exp: i32) -> String {
+    let mut digits = mantissa.unsigned_abs();
+    if digits == 0 {
+        return "0".to_string();
+    }
+    let len = digits.ilog10() + 1;
+    if len > FLOAT_DIGITS {
+        let p = 10u128.pow(len - FLOAT_DIGITS);
+        let rem = digits % p;
+        digits = digits / p + u128::from(rem >= p - rem);
+        exp += (len - FLOAT_DIGITS) as i32;
+    }
+    while digits.is_multiple_of(10) {
+        digits /= 10;
+        exp += 1;
+    }
+
+    let digits = digits.to_string();
+    let sign = if mantissa < 0 { "-" } else { "" };
+    if exp >= 0 {
+        return format!("{}{}{}", sign, digits, "0".repeat(exp as usize));
+    }
+    let frac = exp.unsigned_abs() as usize;
+    if digits.len() > frac {
+        let (int, frac) = digits.split_at(digits.len() - frac);
+        format!("{}{}.{}", sign, int, frac)
+    } else {
+        format!("{}0.{}{}", sign, "0".repeat(frac - digits.len()), digits)
+    }
+}
+
+/// edit the bytes as a vec of `len` bytes of capacity. Converting doesn't copy when the buffer
+/// isn't shared, and the vec grows by doubling, so repeated appends stay cheap.
+fn extend(value: Bytes, len: usize, f: impl FnOnce(&mut Vec<u8>)) -> Bytes {
+    let mut buf = Vec::from(value);
+    buf.reserve(len.saturating_sub(buf.len()));
+    f(&mut buf);
+    Bytes::from(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::SHARED_VALUE_MIN;
+    use crate::Expiry;
+
+    fn options(condition: SetCondition, expiry: ExpiryUpdate, get: bool) -> SetOptions {
+        SetOptions {
+            condition,
+            expiry,
+            get,
+        }
+    }
+
+    #[test]
+    fn test_set_with_conditions() {
+        let backend = Backend::new();
+        let nx = options(SetCondition::IfMissing, ExpiryUpdate::Clear, false);
+        let xx = options(SetCondition::IfExists, ExpiryUpdate::Clear, true);
+
+        assert_eq!(
+            backend.set_with("k".into(), "a".into(), xx),
+            Ok((false, None))
+        );
+        assert_eq!(
+            backend.set_with("k".into(), "a".into(), nx),
+            Ok((true, None))
+        );
+        assert_eq!(
+            backend.set_with("k".into(), "b".into(), nx),
+            Ok((false, None))
+        );
+        assert_eq!(
+            backend.set_with("k".into(), "b".into(), xx),
+            Ok((true, Some("a".into())))
+        );
+        assert_eq!(backend.get(b"k"), Ok(Some("b".into())));
+
+        backend.hset("h".into(), "f".into(), "v".into()).unwrap();
+        assert_eq!(
+            backend.set_with("h".into(), "v".into(), SetOptions { get: true, ..nx }),
+            Err(WrongType)
+        );
+        // without GET the type doesn't matter
+        assert_eq!(
+            backend.set_with("h".into(), "v".into(), SetOptions::default()),
+            Ok((true, None))
+        );
+        assert_eq!(backend.get(b"h"), Ok(Some("v".into())));
+    }
+
+    #[test]
+    fn test_set_with_expiry() {
+        let backend = Backend::new();
+        let at = now_ms() + 10_000;
+        let set = |expiry| {
+            backend
+                .set_with(
+                    "k".into(),
+                    "v".into(),
+                    options(SetCondition::Always, expiry, false),
+                )
+                .unwrap()
+        };
+
+        set(ExpiryUpdate::At(at));
+        assert_eq!(backend.expiry(b"k"), Expiry::At(at));
+        set(ExpiryUpdate::Keep);
+        assert_eq!(backend.expiry(b"k"), Expiry::At(at));
+        set(ExpiryUpdate::Clear);
+        assert_eq!(backend.expiry(b"k"), Expiry::Never);
+        // a deadline already passed leaves nothing to read
+        set(ExpiryUpdate::At(now_ms() - 10));
+        assert_eq!(backend.get(b"k"), Ok(None));
+        set(ExpiryUpdate::Keep);
+        assert_eq!(backend.expiry(b"k"), Expiry::Never);
+    }
+
+    #[test]
+    fn test_incr_by() {
+        let backend = Backend::new();
+        assert_eq!(backend.incr_by("n".into(), 5), Ok(5));
+        assert_eq!(backend.incr_by("n".into(), -7), Ok(-2));
+        assert_eq!(backend.get(b"n"), Ok(Some("-2".into())));
+
+        let at = now_ms() + 10_000;
+        assert!(backend.expire_at(b"n", at, Default::default()));
+        assert_eq!(backend.incr_by("n".into(), 1), Ok(-1));
+        assert_eq!(backend.expiry(b"n"), Expiry::At(at));
+
+        backend.set("max".into(), i64::MAX.to_string().into());
+        assert_eq!(backend.incr_by("max".into(), 1), Err(StringError::Overflow));
+        assert_eq!(backend.incr_by("max".into(), i64::MIN), Ok(-1));
+        for value in ["1.5", " 1", "01", "", "abc"] {
+            backend.set("s".into(), value.into());
+            assert_eq!(
+                backend.incr_by("s".into(), 1),
+                Err(StringError::NotAnInteger)
+            );
+            assert_eq!(backend.get(b"s"), Ok(Some(value.into())));
+        }
+        backend.hset("h".into(), "f".into(), "v".into()).unwrap();
+        assert_eq!(
+            backend.incr_by("h".into(), 1),
+            Err(StringError::WrongType(WrongType))
+        );
+    }
+
+    #[test]
+    fn test_incr_by_float() {
+        let backend = Backend::new();
+        backend.set("f".into(), "10.50".into());
+        assert_eq!(backend.incr_by_float("f".into(), 0.1), Ok("10.6".into()));
+        assert_eq!(backend.incr_by_float("f".into(), -10.6), Ok("0".into()));
+        assert_eq!(backend.incr_by_float("f".into(), 5e3), Ok("5000".into()));
+        assert_eq!(backend.incr_by_float("new".into(), 1.5), Ok("1.5".into()));
+        backend.set("f".into(), "0.2".into());
+        assert_eq!(backend.incr_by_float("f".into(), 0.1), Ok("0.3".into()));
+        assert_eq!(backend.get(b"f"), Ok(Some("0.3".into())));
+        backend.set("f".into(), "1e20".into());
+        assert_eq!(
+            backend.incr_by_float("f".into(), 1.0),
+            Ok("100000000000000000000".into())
+        );
+        // small increments on a large value aren't lost
+        backend.set("f".into(), "1e10".into());
+        for _ in 0..1000 {
+            backend.incr_by_float("f".into(), 0.000001).unwrap();
+        }
+        assert_eq!(backend.get(b"f"), Ok(Some("10000000000.001".into())));
+        backend.set("f".into(), "1".into());
+        assert_eq!(
+            backend.incr_by_float("f".into(), 1e-15),
+            Ok("1.000000000000001".into())
+        );
+        backend.set("f".into(), "123456789".into());
+        assert_eq!(
+            backend.incr_by_float("f".into(), 0.123456789),
+            Ok("123456789.12345679".into())
+        );
+        assert_eq!(
+            backend.incr_by_float("f".into(), -123456789.5),
+            Ok("-0.37654321".into())
+        );
+        // too far apart to add as decimals
+        backend.set("f".into(), "1e300".into());
+        assert_eq!(
+            backend.incr_by_float("f".into(), 1e-300),
+            Ok(Bytes::from(1e300.to_string()))
+        );
+
+        backend.set("f".into(), "1e308".into());
+        assert_eq!(
+            backend.incr_by_float("f".into(), 1e308),
+            Err(StringError::NanOrInfinity)
+        );
+        assert_eq!(
+            backend.incr_by_float("missing".into(), f64::INFINITY),
+            Err(StringError::NanOrInfinity)
+        );
+        // a failed increment doesn't create the key
+        assert_eq!(backend.key_type(b"missing"), None);
+        backend.set("f".into(), "nan".into());
+        assert_eq!(
+            backend.incr_by_float("f".into(), 1.0),
+            Err(StringError::NotAFloat)
+        );
+    }
+
+    #[test]
+    fn test_concurrent_incr() {
+        let backend = Backend::new();
+        let threads = (0..8)
+            .map(|_| {
+                let backend = backend.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..1000 {
+                        backend.incr_by("n".into(), 1).unwrap();
+                        backend.incr_by_float("f".into(), 0.5).unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(backend.get(b"n"), Ok(Some("8000".into())));
+        assert_eq!(backend.get(b"f"), Ok(Some("4000".into())));
+    }
+
+    #[test]
+    fn test_append_strlen() {
+        let backend = Backend::with_limits(crate::RespLimits {
+            max_bulk_len: 8,
+            ..Default::default()
+        });
+        assert_eq!(backend.append("k".into(), b"hello"), Ok(5));
+        assert_eq!(backend.append("k".into(), b" wo"), Ok(8));
+        assert_eq!(backend.append("k".into(), b"r"), Err(StringError::TooLarge));
+        assert_eq!(backend.get(b"k"), Ok(Some("hello wo".into())));
+        assert_eq!(backend.strlen(b"k"), Ok(8));
+        assert_eq!(backend.strlen(b"missing"), Ok(0));
+
+        // an empty append still creates the key
+        assert_eq!(backend.append("empty".into(), b""), Ok(0));
+        assert_eq!(backend.key_type(b"empty"), Some("string"));
+
+        // a value someone else holds is left alone
+        let held = backend.get(b"empty").unwrap().unwrap();
+        assert_eq!(backend.append("empty".into(), b"abc"), Ok(3));
+        assert_eq!(held, "");
+
+        backend.hset("h".into(), "f".into(), "v".into()).unwrap();
+        assert_eq!(backend.strlen(b"h"), Err(WrongType));
+        assert_eq!(
+            backend.append("h".into(), b"x"),
+            Err(StringError::WrongType(WrongType))
+        );
+    }
+
+    #[test]
+    fn test_getrange() {
+        let backend = Backend::new();
+        backend.set("k".into(), "This is a string".into());
+        let range = |start, end| backend.getrange(b"k", start, end).unwrap();
+        assert_eq!(range(0, 3), "This");
+        assert_eq!(range(-3, -1), "ing");
+        assert_eq!(range(0, -1), "This is a string");
+        assert_eq!(range(10, 100), "string");
+        assert_eq!(range(-100, 3), "This");
+        assert_eq!(range(5, 3), "");
+        assert_eq!(range(-1, -5), "");
+        assert_eq!(range(100, 200), "");
+        assert_eq!(backend.getrange(b"missing", 0, -1), Ok(Bytes::new()));
+    }
+
+    #[test]
+    fn test_setrange() {
+        let backend = Backend::with_limits(crate::RespLimits {
+            max_bulk_len: 16,
+            ..Default::default()
+        });
+        backend.set("k".into(), "Hello World".into());
+        assert_eq!(backend.setrange("k".into(), 6, b"Redis"), Ok(11));
+        assert_eq!(backend.get(b"k"), Ok(Some("Hello Redis".into())));
+        assert_eq!(backend.setrange("k".into(), 0, b""), Ok(11));
+
+        assert_eq!(backend.setrange("pad".into(), 3, b"ab"), Ok(5));
+        assert_eq!(backend.get(b"pad"), Ok(Some("\0\0\0ab".into())));
+        assert_eq!(backend.setrange("pad".into(), 1, b"x"), Ok(5));
+        assert_eq!(backend.get(b"pad"), Ok(Some("\0x\0ab".into())));
+
+        assert_eq!(backend.setrange("none".into(), 3, b""), Ok(0));
+        assert_eq!(backend.key_type(b"none"), None);
+        assert_eq!(
+            backend.setrange("none".into(), 15, b"ab"),
+            Err(StringError::TooLarge)
+        );
+        assert_eq!(
+            backend.setrange("k".into(), usize::MAX, b"ab"),
+            Err(StringError::TooLarge)
+        );
+        assert_eq!(backend.key_type(b"none"), None);
+    }
+
+    #[test]
+    fn test_getex_getdel() {
+        let backend = Backend::new();
+        backend.set("k".into(), "v".into());
+        let at = now_ms() + 10_000;
+
+        assert_eq!(
+            backend.getex(b"k", ExpiryUpdate::At(at)),
+            Ok(Some("v".into()))
+        );
+        assert_eq!(backend.expiry(b"k"), Expiry::At(at));
+        assert_eq!(
+            backend.getex(b"k", ExpiryUpdate::Keep),
+            Ok(Some("v".into()))
+        );
+        assert_eq!(backend.expiry(b"k"), Expiry::At(at));
+        assert_eq!(
+            backend.getex(b"k", ExpiryUpdate::Clear),
+            Ok(Some("v".into()))
+        );
+        assert_eq!(backend.expiry(b"k"), Expiry::Never);
+        assert_eq!(backend.getex(b"missing", ExpiryUpdate::Clear), Ok(None));
+
+        assert_eq!(backend.getdel("k".into()), Ok(Some("v".into())));
+        assert_eq!(backend.getdel("k".into()), Ok(None));
+        assert_eq!(backend.expiry(b"k"), Expiry::Missing);
+
+        backend.hset("h".into(), "f".into(), "v".into()).unwrap();
+        assert_eq!(backend.getex(b"h", ExpiryUpdate::Keep), Err(WrongType));
+        assert_eq!(backend.getdel("h".into()), Err(WrongType));
+        assert_eq!(backend.key_type(b"h"), Some("hash"));
+    }
+
+    #[test]
+    fn test_set_detaches_small_values() {
+        let batch = Bytes::from(vec![b'x'; 2 * SHARED_VALUE_MIN]);
+        let in_batch = |b: &[u8]| batch.as_ptr_range().contains(&b.as_ptr());
+        let backend = Backend::new();
+
+        backend.set(batch.slice(..1), batch.slice(1..3));
+        let value = backend.get(b"x").unwrap().unwrap();
+        assert!(!in_batch(&value));
+        assert!(backend.keyspace.iter().all(|e| !in_batch(e.key())));
+
+        // a big value is kept as the slice it was read as, it is most of the buffer anyway
+        let big = batch.slice(SHARED_VALUE_MIN..);
+        backend.set("big".into(), big.clone());
+        assert_eq!(backend.get(b"big").unwrap().unwrap().as_ptr(), big.as_ptr());
+
+        backend
+            .hset(batch.slice(..2), batch.slice(2..3), batch.slice(3..4))
+            .unwrap();
+        let fields = backend.hgetall(b"xx").unwrap();
+        assert!(fields.iter().all(|(f, v)| !in_batch(f) && !in_batch(v)));
+    }
+}
@@ -0,0 +1,176 @@
+use bytes::Bytes;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+/// redis keeps small hashes, lists and sorted sets as a listpack up to this many entries
+const LISTPACK_MAX_ENTRIES: usize = 128;
+/// and as long as no element is longer than this
+const LISTPACK_MAX_VALUE: usize = 64;
+/// sets of integers stay an intset up to this many members
+const INTSET_MAX_ENTRIES: usize = 512;
+/// strings up to this length are allocated along with their object
+const EMBSTR_MAX_LEN: usize = 44;
+
+/// the value stored under a key
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(Bytes),
+    Hash(HashMap<Bytes, Bytes>),
+    List(VecDeque<Bytes>),
+    Set(HashSet<Bytes>),
+    ZSet(ZSet),
+    Stream(Stream),
+}
+
+/// members with their score
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ZSet {
+    pub scores: HashMap<Bytes, f64>,
+}
+
+/// entries ordered by id, each holding its field value pairs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stream {
+    pub entries: BTreeMap<StreamId, Vec<(Bytes, Bytes)>>,
+    pub last_id: StreamId,
+}
+
+/// `<ms>-<seq>`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+impl Value {
+    /// the name TYPE replies with
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Hash(_) => "hash",
+            Value::List(_) => "list",
+            Value::Set(_) => "set",
+            Value::ZSet(_) => "zset",
+            Value::Stream(_) => "stream",
+        }
+    }
+
+    /// the name OBJECT ENCODING replies with. Values aren't stored the way redis stores them, this
+    /// is the encoding redis would pick for the same content.
+    pub fn encoding(&self) -> &'static str {
+        match self {
+            Value::String(s) => {
+                if s.len() <= 20 && parse_int(s).is_some() {
+                    "int"
+                } else if s.len() <= EMBSTR_MAX_LEN {
+                    "embstr"
+                } else {
+                    "raw"
+                }
+            }
+            Value::Hash(h) => {
+                if is_small(h.len(), h.iter().flat_map(|(k, v)| [k, v])) {
+                    "listpack"
+                } else {
+                    "hashtable"
+                }
+            }
+            Value::List(l) => {
+                if is_small(l.len(), l.iter()) {
+                    "listpack"
+                } else {
+                    "quicklist"
+                }
+            }
+            Value::Set(s) => {
+                if s.len() <= INTSET_MAX_ENTRIES && s.iter().all(|m| parse_int(m).is_some()) {
+                    "intset"
+                } else if is_small(s.len(), s.iter()) {
+                    "listpack"
+                } else {
+                    "hashtable"
+                }
+            }
+            Value::ZSet(z) => {
+                if is_small(z.scores.len(), z.scores.keys()) {
+                    "listpack"
+                } else {
+                    "skiplist"
+                }
+            }
+            Value::Stream(_) => "stream",
+        }
+    }
+}
+
+fn is_small<'a>(len: usize, mut items: impl Iterator<Item = &'a Bytes>) -> bool {
+    len <= LISTPACK_MAX_ENTRIES && items.all(|v| v.len() <= LISTPACK_MAX_VALUE)
+}
+
+/// an integer in its canonical form, redis doesn't take `01` or `+1` as integers
+pub(crate) fn parse_int(s: &[u8]) -> Option<i64> {
+    let n = std::str::from_utf8(s).ok()?.parse::<i64>().ok()?;
+    let mut buf = itoa::Buffer::new();
+    (buf.format(n).as_bytes() == s).then_some(n)
+}
+
+/// a float as redis reads one with strtold, without surrounding spaces and never NaN
+pub(crate) fn parse_float(s: &[u8]) -> Option<f64> {
+    let f = std::str::from_utf8(s).ok()?.parse::<f64>().ok()?;
+    (!f.is_nan()).then_some(f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_encoding() {
+        let encoding = |s: &'static [u8]| Value::String(Bytes::from_static(s)).encoding();
+        assert_eq!(encoding(b"12345"), "int");
+        assert_eq!(encoding(b"-9223372036854775808"), "int");
+        assert_eq!(encoding(b"012"), "embstr");
+        assert_eq!(encoding(b"hello"), "embstr");
+        assert_eq!(encoding(&[b'a'; 44]), "embstr");
+        assert_eq!(encoding(&[b'a'; 45]), "raw");
+    }
+
+    #[test]
+    fn test_parse_numbers() {
+        assert_eq!(parse_int(b"-42"), Some(-42));
+        for s in [&b"01"[..], b"+1", b" 1", b"-0", b"", b"9223372036854775808"] {
+            assert_eq!(parse_int(s), None);
+        }
+        assert_eq!(parse_float(b"1.5e3"), Some(1500.0));
+        assert_eq!(parse_float(b"inf"), Some(f64::INFINITY));
+        assert_eq!(parse_float(b"nan"), None);
+        assert_eq!(parse_float(b" 1.5"), None);
+    }
+
+    #[test]
+    fn test_collection_encoding() {
+        let ints = (0..10).map(|i| Bytes::from(i.to_string()));
+        let set = Value::Set(ints.clone().collect());
+        assert_eq!(set.type_name(), "set");
+        assert_eq!(set.encoding(), "intset");
+
+        let set = Value::Set(ints.chain([Bytes::from("a")]).collect());
+        assert_eq!(set.encoding(), "listpack");
+
+        let set = Value::Set((0..200).map(|i| Bytes::from(format!("m{}", i))).collect());
+        assert_eq!(set.encoding(), "hashtable");
+
+        let mut hash = HashMap::new();
+        hash.insert(Bytes::from("f"), Bytes::from("v"));
+        assert_eq!(Value::Hash(hash.clone()).encoding(), "listpack");
+        hash.insert(Bytes::from("big"), Bytes::from(vec![b'x'; 65]));
+        assert_eq!(Value::Hash(hash).encoding(), "hashtable");
+
+        let list = Value::List((0..129).map(|i| Bytes::from(i.to_string())).collect());
+        assert_eq!(list.type_name(), "list");
+        assert_eq!(list.encoding(), "quicklist");
+
+        assert_eq!(Value::ZSet(ZSet::default()).encoding(), "listpack");
+        assert_eq!(Value::Stream(Stream::default()).type_name(), "stream");
+        assert_eq!(Value::Stream(Stream::default()).encoding(), "stream");
+    }
+}
@@ -0,0 +1,416 @@
+use super::{
+    connection::validate_client_name, extract_args, syntax_error, validate_command, ClientCommand,
+    ClientKill, ClientSub, CommandError, CommandExecutor, RESP_OK,
+};
+use crate::{Backend, BulkString, Client, RespArray, RespFrame, Session};
+
+impl CommandExecutor for ClientCommand {
+    fn execute(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        match self.sub {
+            ClientSub::Id => (session.id() as i64).into(),
+            ClientSub::SetName(name) => {
+                session.client.state().name = (!name.is_empty()).then_some(name);
+                RESP_OK.clone()
+            }
+            ClientSub::GetName => match session.name() {
+                Some(name) => BulkString::from(name).into(),
+                None => BulkString::new_null().into(),
+            },
+            ClientSub::List { ids, other_type } => {
+                let list = backend
+                    .clients()
+                    .iter()
+                    .filter(|c| !other_type && ids.as_ref().is_none_or(|ids| ids.contains(&c.id())))
+                    .map(|c| c.info() + "\n")
+                    .collect::<String>();
+                BulkString::from(list).into()
+            }
+            ClientSub::Info => BulkString::from(session.client.info() + "\n").into(),
+            ClientSub::Kill(ClientKill::Addr(addr)) => {
+                match backend.clients().iter().find(|c| c.addr() == addr) {
+                    Some(client) => {
+                        client.kill();
+                        RESP_OK.clone()
+                    }
+                    None => CommandError::Other("No such client".to_string()).into(),
+                }
+            }
+            ClientSub::Kill(ClientKill::Filter {
+                id,
+                other_type,
+                addr,
+                laddr,
+                skipme,
+                maxage,
+            }) => {
+                let matches = |c: &Client| {
+                    !other_type
+                        && id.is_none_or(|id| c.id() == id)
+                        && addr.as_ref().is_none_or(|addr| c.addr() == addr)
+                        && laddr.as_ref().is_none_or(|laddr| c.laddr() == laddr)
+                        && maxage.is_none_or(|maxage| c.age() >= maxage)
+                        && !(skipme && c.id() == session.id())
+                };
+                let mut killed = 0;
+                for client in backend.clients().iter().filter(|c| matches(c)) {
+                    client.kill();
+                    killed += 1;
+                }
+                killed.into()
+            }
+            ClientSub::SetInfo { attr, value } => {
+                let mut state = session.client.state();
+                let value = (!value.is_empty()).then_some(value);
+                match attr {
+                    LIB_NAME => state.lib_name = value,
+                    _ => state.lib_ver = value,
+                }
+                RESP_OK.clone()
+            }
+        }
+    }
+}
+
+const LIB_NAME: &str = "lib-name";
+const LIB_VER: &str = "lib-ver";
+
+impl TryFrom<RespArray> for ClientCommand {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let sub = match value.as_ref().and_then(|vec| vec.get(1)) {
+            Some(RespFrame::BulkString(sub)) => {
+                String::from_utf8_lossy(sub.as_ref()).to_lowercase()
+            }
+            _ => return Err(CommandError::WrongArity("client".to_string())),
+        };
+
+        let sub = match sub.as_str() {
+            "id" => {
+                validate_command(&value, &["client", "id"])?;
+                ClientSub::Id
+            }
+            "setname" => {
+                validate_command(&value, &["client", "setname"])?;
+                let name = extract_strings(value)?.remove(0);
+                validate_client_name(&name)?;
+                ClientSub::SetName(name)
+            }
+            "getname" => {
+                validate_command(&value, &["client", "getname"])?;
+                ClientSub::GetName
+            }
+            "list" => {
+                validate_command(&value, &["client", "list"])?;
+                parse_list(extract_strings(value)?)?
+            }
+            "info" => {
+                validate_command(&value, &["client", "info"])?;
+                ClientSub::Info
+            }
+            "kill" => {
+                validate_command(&value, &["client", "kill"])?;
+                ClientSub::Kill(parse_kill(extract_strings(value)?)?)
+            }
+            "setinfo" => {
+                validate_command(&value, &["client", "setinfo"])?;
+                let mut args = extract_strings(value)?.into_iter();
+                let (attr, value) = (
+                    args.next().unwrap_or_default(),
+                    args.next().unwrap_or_default(),
+                );
+                let attr = match attr.to_ascii_lowercase().as_str() {
+                    LIB_NAME => LIB_NAME,
+                    LIB_VER => LIB_VER,
+                    _ => {
+                        return Err(CommandError::Other(format!(
+                            "Unrecognized option '{}'",
+                            attr
+                        )))
+                    }
+                };
+                if !value.bytes().all(|c| (b'!'..=b'~').contains(&c)) {
+                    return Err(CommandError::Other(format!(
+                        "{} cannot contain spaces, newlines or special characters.",
+                        attr
+                    )));
+                }
+                ClientSub::SetInfo { attr, value }
+            }
+            _ => {
+                return Err(CommandError::UnknownSubcommand {
+                    command: "client".to_string(),
+                    sub,
+                })
+            }
+        };
+        Ok(ClientCommand { sub })
+    }
+}
+
+/// the arguments after the subcommand name
+fn extract_strings(value: RespArray) -> Result<Vec<String>, CommandError> {
+    extract_args(value, 2)?
+        .into_iter()
+        .map(|arg| match arg {
+            RespFrame::BulkString(arg) => Ok(String::from_utf8_lossy(arg.as_ref()).to_string()),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid argument".to_string(),
+            )),
+        })
+        .collect()
+}
+
+/// CLIENT LIST [TYPE normal|master|replica|pubsub] [ID client-id [client-id ...]]
+fn parse_list(args: Vec<String>) -> Result<ClientSub, CommandError> {
+    let mut args = args.into_iter();
+    let (mut ids, mut other_type) = (None, false);
+    while let Some(opt) = args.next() {
+        match opt.to_ascii_lowercase().as_str() {
+            "type" => {
+                let kind = args.next().ok_or_else(syntax_error)?;
+                match kind.to_ascii_lowercase().as_str() {
+                    "normal" => other_type = false,
+                    // every client is a normal one, there is no replication or pub/sub yet
+                    "master" | "replica" | "slave" | "pubsub" => other_type = true,
+                    _ => {
+                        return Err(CommandError::Other(format!(
+                            "Unknown client type '{}'",
+                            kind
+                        )))
+                    }
+                }
+            }
+            "id" => {
+                let list = args
+                    .by_ref()
+                    .map(|id| parse_client_id(&id))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if list.is_empty() {
+                    return Err(syntax_error());
+                }
+                ids = Some(list);
+            }
+            _ => return Err(syntax_error()),
+        }
+    }
+    Ok(ClientSub::List { ids, other_type })
+}
+
+/// CLIENT KILL addr, or CLIENT KILL <filter value> ...
+fn parse_kill(args: Vec<String>) -> Result<ClientKill, CommandError> {
+    if args.len() == 1 {
+        return Ok(ClientKill::Addr(args[0].clone()));
+    }
+    if !args.len().is_multiple_of(2) {
+        return Err(syntax_error());
+    }
+
+    let (mut id, mut addr, mut laddr, mut skipme, mut maxage) = (None, None, None, true, None);
+    let mut other_type = false;
+    for pair in args.chunks(2) {
+        let (opt, value) = (pair[0].to_ascii_lowercase(), &pair[1]);
+        match opt.as_str() {
+            "id" => id = Some(parse_client_id(value)?),
+            "addr" => addr = Some(value.clone()),
+            "laddr" => laddr = Some(value.clone()),
+            "type" => match value.to_ascii_lowercase().as_str() {
+                "normal" => other_type = false,
+                // no such client can exist, match nothing
+                "master" | "replica" | "slave" | "pubsub" => other_type = true,
+                _ => {
+                    return Err(CommandError::Other(format!(
+                        "Unknown client type '{}'",
+                        value
+                    )))
+                }
+            },
+            "user" => {
+                if value != "default" {
+                    return Err(CommandError::Other(format!("No such user '{}'", value)));
+                }
+            }
+            "skipme" => {
+                skipme = match value.to_ascii_lowercase().as_str() {
+                    "yes" => true,
+                    "no" => false,
+                    _ => return Err(syntax_error()),
+                }
+            }
+            "maxage" => {
+                maxage = Some(value.parse().map_err(|_| {
+                    CommandError::Other("value is not an integer or out of range".to_string())
+                })?)
+            }
+            _ => return Err(syntax_error()),
+        }
+    }
+    Ok(ClientKill::Filter {
+        id,
+        other_type,
+        addr,
+        laddr,
+        skipme,
+        maxage,
+    })
+}
+
+fn parse_client_id(id: &str) -> Result<u64, CommandError> {
+    match id.parse::<u64>() {
+        Ok(id) if id > 0 => Ok(id),
+        _ => Err(CommandError::Other("Invalid client ID".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::test_util::{connect, err, exec, exec_in};
+    use crate::SimpleError;
+
+    #[test]
+    fn test_client_id_and_name() {
+        let backend = Backend::new();
+        let mut session = connect(&backend, "127.0.0.1:50000");
+
+        let ret = exec_in(&backend, &mut session, &["CLIENT", "ID"]);
+        assert_eq!(ret, (session.id() as i64).into());
+
+        let ret = exec_in(&backend, &mut session, &["client", "getname"]);
+        assert_eq!(ret, BulkString::new_null().into());
+
+        let ret = exec_in(&backend, &mut session, &["client", "setname", "worker-1"]);
+        assert_eq!(ret, RESP_OK.clone());
+        let ret = exec_in(&backend, &mut session, &["client", "getname"]);
+        assert_eq!(ret, BulkString::from("worker-1").into());
+
+        assert_eq!(
+            exec_in(&backend, &mut session, &["client", "setname", "a b"]),
+            err("Client names cannot contain spaces, newlines or special characters.")
+        );
+    }
+
+    #[test]
+    fn test_client_list_and_info() {
+        let backend = Backend::new();
+        let mut first = connect(&backend, "127.0.0.1:50000");
+        let second = connect(&backend, "127.0.0.1:50001");
+
+        let ret = exec_in(&backend, &mut first, &["client", "list"]);
+        let RespFrame::BulkString(list) = ret else {
+            panic!("expect a bulk string, got {:?}", ret);
+        };
+        let list = String::from_utf8_lossy(list.as_ref()).to_string();
+        let lines = list.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(&format!("id={} addr=127.0.0.1:50000 ", first.id())));
+        assert!(lines[1].starts_with(&format!("id={} addr=127.0.0.1:50001 ", second.id())));
+
+        let id = second.id().to_string();
+        let ret = exec_in(&backend, &mut first, &["client", "list", "id", &id]);
+        assert_eq!(ret, BulkString::from(second.client().info() + "\n").into());
+        let ret = exec_in(
+            &backend,
+            &mut first,
+            &["client", "list", "type", "pubsub", "id", &id],
+        );
+        assert_eq!(ret, BulkString::from("").into());
+        let ret = exec_in(
+            &backend,
+            &mut first,
+            &["client", "list", "type", "normal", "id", &id],
+        );
+        assert_eq!(ret, BulkString::from(second.client().info() + "\n").into());
+
+        let ret = exec_in(&backend, &mut first, &["client", "info"]);
+        assert_eq!(ret, BulkString::from(first.client().info() + "\n").into());
+    }
+
+    #[test]
+    fn test_client_kill() {
+        let backend = Backend::new();
+        let mut first = connect(&backend, "127.0.0.1:50000");
+        let second = connect(&backend, "127.0.0.1:50001");
+        let third = connect(&backend, "127.0.0.1:50002");
+
+        let ret = exec_in(&backend, &mut first, &["client", "kill", "127.0.0.1:50001"]);
+        assert_eq!(ret, RESP_OK.clone());
+        assert!(second.client().is_killed());
+
+        let ret = exec_in(&backend, &mut first, &["client", "kill", "127.0.0.1:1"]);
+        assert_eq!(ret, err("No such client"));
+
+        // skipme defaults to yes
+        let ret = exec_in(
+            &backend,
+            &mut first,
+            &["client", "kill", "laddr", "127.0.0.1:6379"],
+        );
+        assert_eq!(ret, 2.into());
+        assert!(third.client().is_killed());
+        assert!(!first.client().is_killed());
+
+        let id = first.id().to_string();
+        let ret = exec_in(
+            &backend,
+            &mut first,
+            &[
+                "client", "kill", "type", "pubsub", "id", &id, "skipme", "no",
+            ],
+        );
+        assert_eq!(ret, 0.into());
+        assert!(!first.client().is_killed());
+        let ret = exec_in(
+            &backend,
+            &mut first,
+            &["client", "kill", "id", &id, "skipme", "no"],
+        );
+        assert_eq!(ret, 1.into());
+        assert!(first.client().is_killed());
+
+        assert_eq!(
+            exec_in(&backend, &mut first, &["client", "kill"]),
+            err("wrong number of arguments for 'client|kill' command")
+        );
+        assert_eq!(
+            exec_in(&backend, &mut first, &["client", "kill", "foo", "1"]),
+            err("syntax error")
+        );
+    }
+
+    #[test]
+    fn test_client_setinfo() {
+        let backend = Backend::new();
+        let mut session = connect(&backend, "127.0.0.1:50000");
+
+        let ret = exec_in(
+            &backend,
+            &mut session,
+            &["client", "setinfo", "LIB-NAME", "redis-py"],
+        );
+        assert_eq!(ret, RESP_OK.clone());
+        exec_in(
+            &backend,
+            &mut session,
+            &["client", "setinfo", "lib-ver", "5.0.1"],
+        );
+        let info = session.client().info();
+        assert!(info.ends_with("lib-name=redis-py lib-ver=5.0.1"));
+
+        assert_eq!(
+            exec_in(
+                &backend,
+                &mut session,
+                &["client", "setinfo", "lib-foo", "x"],
+            ),
+            err("Unrecognized option 'lib-foo'")
+        );
+    }
+
+    #[test]
+    fn test_client_unknown_subcommand() {
+        assert_eq!(
+            exec(&Backend::new(), &["client", "foo"]),
+            SimpleError::new("ERR unknown subcommand 'foo'. Try CLIENT HELP.").into()
+        );
+    }
+}
@@ -0,0 +1,274 @@
+use super::{
+    extract_args, lookup_command, validate_command, CommandError, CommandExecutor,
+    CommandIntrospect, CommandSpec, IntrospectSub, COMMAND_TABLE,
+};
+use crate::{
+    Backend, BulkString, RespArray, RespFrame, RespMap, RespNull, RespSet, Session, SimpleString,
+};
+
+impl CommandExecutor for CommandIntrospect {
+    fn execute(self, _backend: &Backend, _session: &mut Session) -> RespFrame {
+        match self.sub {
+            IntrospectSub::List => {
+                RespArray::new(COMMAND_TABLE.iter().map(command_info).collect::<Vec<_>>()).into()
+            }
+            IntrospectSub::Count => (COMMAND_TABLE.len() as i64).into(),
+            IntrospectSub::Info(names) if names.is_empty() => {
+                RespArray::new(COMMAND_TABLE.iter().map(command_info).collect::<Vec<_>>()).into()
+            }
+            IntrospectSub::Info(names) => RespArray::new(
+                names
+                    .iter()
+                    .map(|name| match lookup_spec(name) {
+                        Some(spec) => command_info(spec),
+                        None => RespNull.into(),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            IntrospectSub::Docs(names) => {
+                let mut map = RespMap::new();
+                if names.is_empty() {
+                    for spec in COMMAND_TABLE {
+                        map.insert(spec.name, command_docs(spec));
+                    }
+                }
+                for name in names {
+                    if let Some(spec) = lookup_spec(&name) {
+                        map.insert(spec.name, command_docs(spec));
+                    }
+                }
+                map.into()
+            }
+            IntrospectSub::GetKeys(args) => match get_keys(args) {
+                Ok(keys) => RespArray::new(keys).into(),
+                Err(e) => e.into(),
+            },
+        }
+    }
+}
+
+impl TryFrom<RespArray> for CommandIntrospect {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let sub = match value.as_ref().and_then(|vec| vec.get(1)) {
+            Some(RespFrame::BulkString(sub)) => String::from_utf8_lossy(sub.as_ref()).to_string(),
+            Some(_) => {
+                return Err(CommandError::InvalidArgument(
+                    "Invalid subcommand".to_string(),
+                ))
+            }
+            None => {
+                validate_command(&value, &["command"])?;
+                return Ok(CommandIntrospect {
+                    sub: IntrospectSub::List,
+                });
+            }
+        };
+
+        let sub = match sub.to_ascii_lowercase().as_str() {
+            "count" => {
+                validate_command(&value, &["command", "count"])?;
+                IntrospectSub::Count
+            }
+            "info" => {
+                validate_command(&value, &["command", "info"])?;
+                IntrospectSub::Info(extract_names(value)?)
+            }
+            "docs" => {
+                validate_command(&value, &["command", "docs"])?;
+                IntrospectSub::Docs(extract_names(value)?)
+            }
+            "getkeys" => {
+                validate_command(&value, &["command", "getkeys"])?;
+                IntrospectSub::GetKeys(extract_args(value, 2)?)
+            }
+            _ => {
+                return Err(CommandError::UnknownSubcommand {
+                    command: "command".to_string(),
+                    sub,
+                })
+            }
+        };
+        Ok(CommandIntrospect { sub })
+    }
+}
+
+fn extract_names(value: RespArray) -> Result<Vec<String>, CommandError> {
+    extract_args(value, 2)?
+        .into_iter()
+        .map(|arg| match arg {
+            RespFrame::BulkString(name) => Ok(String::from_utf8_lossy(name.as_ref()).to_string()),
+            _ => Err(CommandError::InvalidArgument(
+                "Invalid command name".to_string(),
+            )),
+        })
+        .collect()
+}
+
+/// look up a command, or a subcommand given as `container|sub`
+fn lookup_spec(name: &str) -> Option<&'static CommandSpec> {
+    match name.split_once('|') {
+        Some((container, sub)) => lookup_command(container)?.subcommand(sub),
+        None => lookup_command(name),
+    }
+}
+
+fn get_keys(args: Vec<RespFrame>) -> Result<Vec<RespFrame>, CommandError> {
+    let spec = match args.first() {
+        Some(RespFrame::BulkString(name)) => {
+            lookup_command(&String::from_utf8_lossy(name.as_ref()))
+        }
+        _ => None,
+    }
+    .ok_or_else(|| CommandError::Other("Invalid command specified".to_string()))?;
+
+    let spec = match args.get(1) {
+        Some(RespFrame::BulkString(sub)) if !spec.subcommands.is_empty() => spec
+            .subcommand(&String::from_utf8_lossy(sub.as_ref()))
+            .ok_or_else(|| CommandError::Other("Invalid subcommand specified".to_string()))?,
+        _ => spec,
+    };
+
+    if !spec.check_arity(args.len()) {
+        return Err(CommandError::Other(
+            "Invalid number of arguments specified for command".to_string(),
+        ));
+    }
+
+    let keys = spec.key_positions(args.len());
+    if keys.is_empty() {
+        return Err(CommandError::Other(
+            "The command has no key arguments".to_string(),
+        ));
+    }
+    Ok(keys.into_iter().map(|i| args[i].clone()).collect())
+}
+
+fn command_info(spec: &CommandSpec) -> RespFrame {
+    RespArray::new(vec![
+        BulkString::from(spec.name).into(),
+        spec.arity.into(),
+        simple_string_set(spec.flags),
+        spec.first_key.into(),
+        spec.last_key.into(),
+        spec.step.into(),
+        simple_string_set(spec.acl_categories),
+        // tips and key specifications are not tracked
+        RespArray::new([]).into(),
+        RespArray::new([]).into(),
+        RespArray::new(
+            spec.subcommands
+                .iter()
+                .map(command_info)
+                .collect::<Vec<_>>(),
+        )
+        .into(),
+    ])
+    .into()
+}
+
+fn command_docs(spec: &CommandSpec) -> RespFrame {
+    let mut map = RespMap::new();
+    map.insert("summary", BulkString::from(spec.summary));
+    map.insert("since", BulkString::from(spec.since));
+    map.insert("group", BulkString::from(spec.group));
+    map.insert("complexity", BulkString::from(spec.complexity));
+    if !spec.subcommands.is_empty() {
+        let mut subcommands = RespMap::new();
+        for sub in spec.subcommands {
+            subcommands.insert(sub.name, command_docs(sub));
+        }
+        map.insert("subcommands", subcommands);
+    }
+    map.into()
+}
+
+fn simple_string_set(values: &[&'static str]) -> RespFrame {
+    RespSet::new(
+        values
+            .iter()
+            .map(|v| SimpleString::new(*v).into())
+            .collect::<Vec<RespFrame>>(),
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::test_util::{err, exec};
+    use crate::SimpleError;
+
+    #[test]
+    fn test_case_insensitive_dispatch() {
+        let ret = exec(&Backend::new(), &["ECHO", "hello"]);
+        assert_eq!(ret, BulkString::from("hello").into());
+    }
+
+    #[test]
+    fn test_command_count() {
+        let ret = exec(&Backend::new(), &["command", "COUNT"]);
+        assert_eq!(ret, (COMMAND_TABLE.len() as i64).into());
+    }
+
+    #[test]
+    fn test_command_info() {
+        let ret = exec(&Backend::new(), &["command", "info", "get", "nope"]);
+        let RespFrame::Array(RespArray(Some(infos))) = ret else {
+            panic!("expect an array, got {:?}", ret);
+        };
+        assert_eq!(infos.len(), 2);
+        assert_eq!(infos[1], RespNull.into());
+
+        let RespFrame::Array(RespArray(Some(info))) = &infos[0] else {
+            panic!("expect an array, got {:?}", infos[0]);
+        };
+        assert_eq!(info[0], BulkString::from("get").into());
+        assert_eq!(info[1], 2.into());
+        assert_eq!(
+            info[2],
+            RespSet::new([
+                SimpleString::new("readonly").into(),
+                SimpleString::new("fast").into()
+            ])
+            .into()
+        );
+        assert_eq!(&info[3..6], &[1.into(), 1.into(), 1.into()]);
+    }
+
+    #[test]
+    fn test_command_docs() {
+        let ret = exec(&Backend::new(), &["command", "docs", "hset"]);
+        let RespFrame::Map(map) = ret else {
+            panic!("expect a map, got {:?}", ret);
+        };
+        let RespFrame::Map(docs) = &map["hset"] else {
+            panic!("expect a map, got {:?}", map["hset"]);
+        };
+        assert_eq!(docs["group"], BulkString::from("hash").into());
+    }
+
+    #[test]
+    fn test_command_getkeys() {
+        let ret = exec(
+            &Backend::new(),
+            &["command", "getkeys", "set", "hello", "world"],
+        );
+        assert_eq!(
+            ret,
+            RespArray::new([BulkString::from("hello").into()]).into()
+        );
+
+        let ret = exec(&Backend::new(), &["command", "getkeys", "echo", "a"]);
+        assert_eq!(ret, err("The command has no key arguments"));
+    }
+
+    #[test]
+    fn test_command_unknown_subcommand() {
+        assert_eq!(
+            exec(&Backend::new(), &["command", "foo"]),
+            SimpleError::new("ERR unknown subcommand 'foo'. Try COMMAND HELP.").into()
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use super::{extract_args, validate_command, CommandError, CommandExecutor, Hello};
+use crate::{Backend, BulkString, RespArray, RespFrame, RespMap, RespVersion, Session};
+
+const SERVER_NAME: &str = "redis";
+/// the redis version whose behavior we follow, clients use it to pick features
+const SERVER_VERSION: &str = "7.2.0";
+const DEFAULT_USER: &str = "default";
+
+impl CommandExecutor for Hello {
+    fn execute(self, _backend: &Backend, session: &mut Session) -> RespFrame {
+        if let Some((user, _password)) = &self.auth {
+            // there is no ACL, the default user has no password
+            if user != DEFAULT_USER {
+                return CommandError::WrongPass.into();
+            }
+        }
+        let mut state = session.client.state();
+        if let Some(name) = self.setname {
+            state.name = (!name.is_empty()).then_some(name);
+        }
+        if let Some(protover) = self.protover {
+            state.protocol = protover;
+        }
+
+        let proto = match state.protocol {
+            RespVersion::Resp2 => 2,
+            RespVersion::Resp3 => 3,
+        };
+        let mut map = RespMap::new();
+        map.insert("server", BulkString::from(SERVER_NAME));
+        map.insert("version", BulkString::from(SERVER_VERSION));
+        map.insert("proto", proto);
+        map.insert("id", session.id() as i64);
+        map.insert("mode", BulkString::from("standalone"));
+        map.insert("role", BulkString::from("master"));
+        map.insert("modules", RespArray::new([]));
+        map.into()
+    }
+}
+
+impl TryFrom<RespArray> for Hello {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hello"])?;
+
+        let mut args = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| match arg {
+                RespFrame::BulkString(arg) => Ok(String::from_utf8_lossy(arg.as_ref()).to_string()),
+                _ => Err(CommandError::InvalidArgument(
+                    "Invalid argument".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter();
+
+        let protover = match args.next() {
+            Some(v) => Some(v.parse::<i64>().map_err(|_| {
+                CommandError::Other(
+                    "Protocol version is not an integer or out of range".to_string(),
+                )
+            })?),
+            None => None,
+        };
+
+        let mut hello = Hello {
+            protover: None,
+            auth: None,
+            setname: None,
+        };
+        while let Some(opt) = args.next() {
+            match opt.to_ascii_lowercase().as_str() {
+                "auth" => match (args.next(), args.next()) {
+                    (Some(user), Some(password)) => hello.auth = Some((user, password)),
+                    _ => return Err(hello_syntax_error(&opt)),
+                },
+                "setname" => match args.next() {
+                    Some(name) => {
+                        validate_client_name(&name)?;
+                        hello.setname = Some(name);
+                    }
+                    None => return Err(hello_syntax_error(&opt)),
+                },
+                _ => return Err(hello_syntax_error(&opt)),
+            }
+        }
+
+        hello.protover = match protover {
+            None => None,
+            Some(2) => Some(RespVersion::Resp2),
+            Some(3) => Some(RespVersion::Resp3),
+            Some(_) => {
+                return Err(CommandError::NoProto(
+                    "unsupported protocol version".to_string(),
+                ))
+            }
+        };
+        Ok(hello)
+    }
+}
+
+fn hello_syntax_error(opt: &str) -> CommandError {
+    CommandError::Other(format!("Syntax error in HELLO option '{}'", opt))
+}
+
+/// client names are a single printable word, so they can't break CLIENT LIST output
+pub(crate) fn validate_client_name(name: &str) -> Result<(), CommandError> {
+    if name.bytes().all(|c| (b'!'..=b'~').contains(&c)) {
+        Ok(())
+    } else {
+        Err(CommandError::Other(
+            "Client names cannot contain spaces, newlines or special characters.".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::test_util::{err, exec, exec_in};
+    use crate::SimpleError;
+
+    #[test]
+    fn test_hello_switch_protocol() {
+        let mut session = Session::new();
+        assert_eq!(session.protocol(), RespVersion::Resp2);
+
+        let ret = exec_in(
+            &Backend::new(),
+            &mut session,
+            &["HELLO", "3", "setname", "worker"],
+        );
+        assert_eq!(session.protocol(), RespVersion::Resp3);
+        assert_eq!(session.name().as_deref(), Some("worker"));
+
+        let RespFrame::Map(map) = ret else {
+            panic!("expect a map, got {:?}", ret);
+        };
+        assert_eq!(map["proto"], 3.into());
+        assert_eq!(map["id"], (session.id() as i64).into());
+    }
+
+    #[test]
+    fn test_hello_without_version_keeps_protocol() {
+        let mut session = Session::new();
+        exec_in(&Backend::new(), &mut session, &["hello"]);
+        assert_eq!(session.protocol(), RespVersion::Resp2);
+    }
+
+    #[test]
+    fn test_hello_errors() {
+        let backend = Backend::new();
+        assert_eq!(
+            exec(&backend, &["hello", "4"]),
+            SimpleError::new("NOPROTO unsupported protocol version").into()
+        );
+        assert_eq!(
+            exec(&backend, &["hello", "abc"]),
+            err("Protocol version is not an integer or out of range")
+        );
+        assert_eq!(
+            exec(&backend, &["hello", "3", "auth"]),
+            err("Syntax error in HELLO option 'auth'")
+        );
+        assert_eq!(
+            exec(&backend, &["hello", "3", "auth", "bob", "p"]),
+            SimpleError::new("WRONGPASS invalid username-password pair or user is disabled.")
+                .into()
+        );
+    }
+}
@@ -0,0 +1,338 @@
+use super::{
+    bulk_arg, command_name, extract_args, int_arg, invalid_expire_time, validate_command,
+    CommandExecutor, Expire, Object, ObjectSub, Persist, Ttl, Type,
+};
+use crate::{
+    cmd::CommandError, now_ms, Backend, ExpireOptions, Expiry, RespArray, RespFrame, RespNull,
+    Session, SimpleString,
+};
+
+impl CommandExecutor for Type {
+    fn execute(self, backend: &Backend, _session: &mut Session) -> RespFrame {
+        SimpleString::new(backend.key_type(&self.key).unwrap_or("none")).into()
+    }
+}
+
+impl CommandExecutor for Object {
+    fn execute(self, backend: &Backend, _session: &mut Session) -> RespFrame {
+        match self.sub {
+            ObjectSub::Encoding(key) => match backend.encoding(&key) {
+                Some(encoding) => RespFrame::BulkString(encoding.into()),
+                None => RespFrame::Null(RespNull),
+            },
+        }
+    }
+}
+
+impl CommandExecutor for Expire {
+    fn execute(self, backend: &Backend, _session: &mut Session) -> RespFrame {
+        let at = match self.absolute {
+            true => Some(self.millis),
+            false => now_ms().checked_add(self.millis),
+        };
+        match at {
+            Some(at) => i64::from(backend.expire_at(&self.key, at, self.options)).into(),
+            None => invalid_expire_time(self.name).into(),
+        }
+    }
+}
+
+impl CommandExecutor for Ttl {
+    fn execute(self, backend: &Backend, _session: &mut Session) -> RespFrame {
+        let at = match backend.expiry(&self.key) {
+            Expiry::Missing => return RespFrame::Integer(-2),
+            Expiry::Never => return RespFrame::Integer(-1),
+            Expiry::At(at) => at,
+        };
+        let reply = match (self.absolute, self.millis) {
+            (true, true) => at,
+            (true, false) => at / 1000,
+            (false, true) => (at - now_ms()).max(0),
+            (false, false) => ((at - now_ms()).max(0) + 500) / 1000,
+        };
+        reply.into()
+    }
+}
+
+impl CommandExecutor for Persist {
+    fn execute(self, backend: &Backend, _session: &mut Session) -> RespFrame {
+        i64::from(backend.persist(&self.key)).into()
+    }
+}
+
+impl TryFrom<RespArray> for Expire {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // seconds or milliseconds, relative or a unix time
+        let (name, unit, absolute) = match command_name(&value)?.as_str() {
+            "expire" => ("expire", 1000, false),
+            "pexpire" => ("pexpire", 1, false),
+            "expireat" => ("expireat", 1000, true),
+            "pexpireat" => ("pexpireat", 1, true),
+            name => return Err(CommandError::InvalidCommand(name.to_string())),
+        };
+        validate_command(&value, &[name])?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = bulk_arg(args.next())?;
+        let time = int_arg(&bulk_arg(args.next())?)?;
+        let mut options = ExpireOptions::default();
+        for arg in args {
+            let arg = bulk_arg(Some(arg))?;
+            match arg.to_ascii_lowercase().as_slice() {
+                b"nx" => options.nx = true,
+                b"xx" => options.xx = true,
+                b"gt" => options.gt = true,
+                b"lt" => options.lt = true,
+                _ => {
+                    return Err(CommandError::Other(format!(
+                        "Unsupported option {}",
+                        String::from_utf8_lossy(&arg)
+                    )))
+                }
+            }
+        }
+        if options.nx && (options.xx || options.gt || options.lt) {
+            return Err(CommandError::Other(
+                "NX and XX, GT or LT options at the same time are not compatible".to_string(),
+            ));
+        }
+        if options.gt && options.lt {
+            return Err(CommandError::Other(
+                "GT and LT options at the same time are not compatible".to_string(),
+            ));
+        }
+
+        Ok(Expire {
+            name,
+            key,
+            millis: time
+                .checked_mul(unit)
+                .ok_or_else(|| invalid_expire_time(name))?,
+            absolute,
+            options,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Ttl {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (name, millis, absolute) = match command_name(&value)?.as_str() {
+            "ttl" => ("ttl", false, false),
+            "pttl" => ("pttl", true, false),
+            "expiretime" => ("expiretime", false, true),
+            "pexpiretime" => ("pexpiretime", true, true),
+            name => return Err(CommandError::InvalidCommand(name.to_string())),
+        };
+        validate_command(&value, &[name])?;
+
+        let key = bulk_arg(extract_args(value, 1)?.into_iter().next())?;
+        Ok(Ttl {
+            key,
+            millis,
+            absolute,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Persist {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["persist"])?;
+
+        let key = bulk_arg(extract_args(value, 1)?.into_iter().next())?;
+        Ok(Persist { key })
+    }
+}
+
+impl TryFrom<RespArray> for Type {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["type"])?;
+
+        match extract_args(value, 1)?.into_iter().next() {
+            Some(RespFrame::BulkString(key)) => match key.0 {
+                Some(key) => Ok(Type { key }),
+                None => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+            },
+            _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Object {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let sub = match value.as_ref().and_then(|vec| vec.get(1)) {
+            Some(RespFrame::BulkString(sub)) => {
+                String::from_utf8_lossy(sub.as_ref()).to_lowercase()
+            }
+            _ => return Err(CommandError::WrongArity("object".to_string())),
+        };
+
+        match sub.as_str() {
+            "encoding" => {
+                validate_command(&value, &["object", "encoding"])?;
+                match extract_args(value, 2)?.into_iter().next() {
+                    Some(RespFrame::BulkString(key)) => match key.0 {
+                        Some(key) => Ok(Object {
+                            sub: ObjectSub::Encoding(key),
+                        }),
+                        None => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+                    },
+                    _ => Err(CommandError::InvalidArgument("Invalid key".to_string())),
+                }
+            }
+            _ => Err(CommandError::UnknownSubcommand {
+                command: "object".to_string(),
+                sub,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cmd::{
+            test_util::{err, exec},
+            Command,
+        },
+        BulkString, RespDecode,
+    };
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_type_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("s".into(), "v".into());
+        backend.hset("h".into(), "f".into(), "v".into())?;
+
+        let reply = exec(&backend, &["type", "s"]);
+        assert_eq!(reply, SimpleString::new("string").into());
+        let reply = exec(&backend, &["TYPE", "h"]);
+        assert_eq!(reply, SimpleString::new("hash").into());
+        let reply = exec(&backend, &["type", "x"]);
+        assert_eq!(reply, SimpleString::new("none").into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_encoding_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("n".into(), "123".into());
+        backend.set("s".into(), "hello".into());
+        backend.hset("h".into(), "f".into(), "v".into())?;
+
+        let reply = exec(&backend, &["object", "encoding", "n"]);
+        assert_eq!(reply, BulkString::from("int").into());
+        let reply = exec(&backend, &["OBJECT", "ENCODING", "s"]);
+        assert_eq!(reply, BulkString::from("embstr").into());
+        let reply = exec(&backend, &["object", "encoding", "h"]);
+        assert_eq!(reply, BulkString::from("listpack").into());
+        let reply = exec(&backend, &["object", "encoding", "x"]);
+        assert_eq!(reply, RespFrame::Null(RespNull));
+
+        let mut buf = BytesMut::from(&b"*3\r\n$6\r\nobject\r\n$4\r\nfreq\r\n$1\r\nx\r\n"[..]);
+        let err = Command::try_from(RespArray::decode(&mut buf)?).unwrap_err();
+        assert!(matches!(err, CommandError::UnknownSubcommand { .. }));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_ttl_commands() {
+        let backend = Backend::new();
+        backend.set("k".into(), "v".into());
+
+        assert_eq!(exec(&backend, &["ttl", "k"]), RespFrame::Integer(-1));
+        assert_eq!(exec(&backend, &["ttl", "missing"]), RespFrame::Integer(-2));
+        assert_eq!(
+            exec(&backend, &["expire", "k", "100"]),
+            RespFrame::Integer(1)
+        );
+        assert_eq!(exec(&backend, &["ttl", "k"]), RespFrame::Integer(100));
+        assert!(matches!(
+            exec(&backend, &["pttl", "k"]),
+            RespFrame::Integer(99_000..=100_000)
+        ));
+        assert_eq!(
+            exec(&backend, &["expire", "k", "50", "GT"]),
+            RespFrame::Integer(0)
+        );
+        assert_eq!(
+            exec(&backend, &["expire", "k", "50", "xx", "lt"]),
+            RespFrame::Integer(1)
+        );
+        assert_eq!(
+            exec(&backend, &["expire", "missing", "50"]),
+            RespFrame::Integer(0)
+        );
+
+        let at = (now_ms() / 1000 + 1000).to_string();
+        assert_eq!(
+            exec(&backend, &["expireat", "k", &at]),
+            RespFrame::Integer(1)
+        );
+        assert_eq!(
+            exec(&backend, &["expiretime", "k"]),
+            RespFrame::Integer(at.parse().unwrap())
+        );
+        assert_eq!(
+            exec(&backend, &["pexpiretime", "k"]),
+            RespFrame::Integer(at.parse::<i64>().unwrap() * 1000)
+        );
+
+        assert_eq!(exec(&backend, &["persist", "k"]), RespFrame::Integer(1));
+        assert_eq!(exec(&backend, &["persist", "k"]), RespFrame::Integer(0));
+        assert_eq!(exec(&backend, &["expiretime", "k"]), RespFrame::Integer(-1));
+
+        // a time in the past deletes the key
+        assert_eq!(
+            exec(&backend, &["pexpire", "k", "-1"]),
+            RespFrame::Integer(1)
+        );
+        assert_eq!(
+            exec(&backend, &["type", "k"]),
+            SimpleString::new("none").into()
+        );
+        assert_eq!(
+            exec(&backend, &["pexpireat", "k", "0"]),
+            RespFrame::Integer(0)
+        );
+    }
+
+    #[test]
+    fn test_expire_errors() {
+        let backend = Backend::new();
+        backend.set("k".into(), "v".into());
+
+        assert_eq!(
+            exec(&backend, &["expire", "k", "10", "nx", "gt"]),
+            err("NX and XX, GT or LT options at the same time are not compatible")
+        );
+        assert_eq!(
+            exec(&backend, &["expire", "k", "10", "gt", "lt"]),
+            err("GT and LT options at the same time are not compatible")
+        );
+        assert_eq!(
+            exec(&backend, &["expire", "k", "10", "foo"]),
+            err("Unsupported option foo")
+        );
+        assert_eq!(
+            exec(&backend, &["expire", "k", "+10"]),
+            err("value is not an integer or out of range")
+        );
+        assert_eq!(
+            exec(&backend, &["expire", "k", "9223372036854775807"]),
+            err("invalid expire time in 'expire' command")
+        );
+        assert_eq!(
+            exec(&backend, &["pexpire", "k", "9223372036854775807"]),
+            err("invalid expire time in 'pexpire' command")
+        );
+        assert_eq!(exec(&backend, &["ttl", "k"]), RespFrame::Integer(-1));
+    }
+}
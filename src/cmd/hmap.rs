@@ -1,62 +1,58 @@
 use super::{extract_args, validate_command, CommandExecutor, HGet, HGetAll, HSet, RESP_OK};
-use crate::{cmd::CommandError, BulkString, RespArray, RespFrame};
+use crate::{cmd::CommandError, BulkString, RespArray, RespFrame, RespMap};
 
 impl CommandExecutor for HGet {
-    fn execute(self, backend: &crate::Backend) -> RespFrame {
-        backend
-            .hget(&self.key, &self.field)
-            .unwrap_or(RespFrame::Null(crate::RespNull))
+    fn execute(self, backend: &crate::Backend, _session: &mut crate::Session) -> RespFrame {
+        match backend.hget(&self.key, &self.field) {
+            Ok(Some(value)) => RespFrame::BulkString(value.into()),
+            Ok(None) => RespFrame::Null(crate::RespNull),
+            Err(e) => CommandError::from(e).into(),
+        }
     }
 }
 
 impl CommandExecutor for HGetAll {
-    fn execute(self, backend: &crate::Backend) -> RespFrame {
-        let hmap = backend.hmap.get(&self.key);
-
-        match hmap {
-            Some(hmap) => {
-                let mut data = Vec::with_capacity(hmap.len());
-                for v in hmap.iter() {
-                    let key = v.key().to_owned();
-                    data.push((key, v.value().clone()));
-                }
-                if self.sort {
-                    data.sort_by(|a, b| a.0.cmp(&b.0));
-                }
-                let ret = data
-                    .into_iter()
-                    .flat_map(|(k, v)| vec![BulkString::from(k).into(), v])
-                    .collect::<Vec<RespFrame>>();
-
-                RespArray::new(ret).into()
-            }
-            None => RespArray::new([]).into(),
+    fn execute(self, backend: &crate::Backend, _session: &mut crate::Session) -> RespFrame {
+        let mut data = match backend.hgetall(&self.key) {
+            Ok(data) => data,
+            Err(e) => return CommandError::from(e).into(),
+        };
+        if self.sort {
+            data.sort_by(|a, b| a.0.cmp(&b.0));
         }
+        // a map for RESP3 clients, RESP2 ones get it flattened into an array
+        let mut map = RespMap::with_capacity(data.len());
+        for (k, v) in data {
+            map.push(BulkString::from(k), BulkString::from(v));
+        }
+        map.into()
     }
 }
 
 impl CommandExecutor for HSet {
-    fn execute(self, backend: &crate::Backend) -> RespFrame {
-        backend.hset(self.key, self.field, self.value);
-        RESP_OK.clone()
+    fn execute(self, backend: &crate::Backend, _session: &mut crate::Session) -> RespFrame {
+        match backend.hset(self.key, self.field, self.value) {
+            Ok(_) => RESP_OK.clone(),
+            Err(e) => CommandError::from(e).into(),
+        }
     }
 }
 
 impl TryFrom<RespArray> for HGet {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hget"], 2)?;
+        validate_command(&value, &["hget"])?;
 
         let mut args = extract_args(value, 1)?.into_iter();
         match (args.next(), args.next()) {
             (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field))) => {
                 let key = match key.0 {
-                    Some(k) => String::from_utf8(k)?,
+                    Some(k) => k,
                     None => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
                 };
 
                 let field = match field.0 {
-                    Some(k) => String::from_utf8(k)?,
+                    Some(k) => k,
                     None => return Err(CommandError::InvalidArgument("Invalid field".to_string())),
                 };
                 Ok(HGet { key, field })
@@ -71,13 +67,13 @@ impl TryFrom<RespArray> for HGet {
 impl TryFrom<RespArray> for HGetAll {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hgetall"], 1)?;
+        validate_command(&value, &["hgetall"])?;
 
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => {
                 let key = match key.0 {
-                    Some(k) => String::from_utf8(k)?,
+                    Some(k) => k,
                     None => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
                 };
                 Ok(HGetAll { key, sort: false })
@@ -90,20 +86,29 @@ impl TryFrom<RespArray> for HGetAll {
 impl TryFrom<RespArray> for HSet {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hset"], 3)?;
+        validate_command(&value, &["hset"])?;
 
         let mut args = extract_args(value, 1)?.into_iter();
         match (args.next(), args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field)), Some(value)) => {
+            (
+                Some(RespFrame::BulkString(key)),
+                Some(RespFrame::BulkString(field)),
+                Some(RespFrame::BulkString(value)),
+            ) => {
                 let key = match key.0 {
-                    Some(k) => String::from_utf8(k)?,
+                    Some(k) => k,
                     None => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
                 };
 
                 let field = match field.0 {
-                    Some(k) => String::from_utf8(k)?,
+                    Some(k) => k,
                     None => return Err(CommandError::InvalidArgument("Invalid field".to_string())),
                 };
+
+                let value = match value.0 {
+                    Some(v) => v,
+                    None => return Err(CommandError::InvalidArgument("Invalid value".to_string())),
+                };
                 Ok(HSet { key, field, value })
             }
             _ => Err(CommandError::InvalidArgument(
@@ -118,7 +123,9 @@ mod tests {
     use crate::RespDecode;
 
     use super::*;
+    use crate::Session;
     use anyhow::Result;
+    use bytes::Bytes;
     use bytes::BytesMut;
 
     #[test]
@@ -158,7 +165,7 @@ mod tests {
         let result: HSet = frame.try_into()?;
         assert_eq!(result.key, "map");
         assert_eq!(result.field, "hello");
-        assert_eq!(result.value, RespFrame::BulkString(b"world".into()));
+        assert_eq!(result.value, "world");
 
         Ok(())
     }
@@ -167,32 +174,37 @@ mod tests {
     fn test_hset_hget_hgetall_commands() -> Result<()> {
         let backend = crate::Backend::new();
         let cmd = HSet {
-            key: "map".to_string(),
-            field: "hello".to_string(),
-            value: RespFrame::BulkString(b"world".into()),
+            key: Bytes::from_static(b"map"),
+            field: Bytes::from_static(b"hello"),
+            value: Bytes::from_static(b"world"),
         };
-        let result = cmd.execute(&backend);
+        let result = cmd.execute(&backend, &mut Session::default());
         assert_eq!(result, RESP_OK.clone());
 
         let cmd = HSet {
-            key: "map".to_string(),
-            field: "hello1".to_string(),
-            value: RespFrame::BulkString(b"world1".into()),
+            key: Bytes::from_static(b"map"),
+            field: Bytes::from_static(b"hello1"),
+            value: Bytes::from_static(b"world1"),
         };
-        cmd.execute(&backend);
+        cmd.execute(&backend, &mut Session::default());
 
         let cmd = HGet {
-            key: "map".to_string(),
-            field: "hello".to_string(),
+            key: Bytes::from_static(b"map"),
+            field: Bytes::from_static(b"hello"),
         };
-        let result = cmd.execute(&backend);
+        let result = cmd.execute(&backend, &mut Session::default());
         assert_eq!(result, RespFrame::BulkString(b"world".into()));
 
         let cmd = HGetAll {
-            key: "map".to_string(),
+            key: Bytes::from_static(b"map"),
             sort: true,
         };
-        let result = cmd.execute(&backend);
+        let result = cmd.execute(&backend, &mut Session::default());
+
+        let mut expected = RespMap::new();
+        expected.insert(BulkString::from("hello"), BulkString::from("world"));
+        expected.insert(BulkString::from("hello1"), BulkString::from("world1"));
+        assert_eq!(result, expected.into());
 
         let expected = RespArray::new([
             BulkString::from("hello").into(),
@@ -200,7 +212,33 @@ mod tests {
             BulkString::from("hello1").into(),
             BulkString::from("world1").into(),
         ]);
-        assert_eq!(result, expected.into());
+        assert_eq!(result.into_resp2(), expected.into());
         Ok(())
     }
+
+    #[test]
+    fn test_hash_commands_wrong_type() {
+        let backend = crate::Backend::new();
+        backend.set("str".into(), "v".into());
+        let wrong_type: RespFrame = CommandError::WrongType.into();
+
+        let cmd = HSet {
+            key: Bytes::from_static(b"str"),
+            field: Bytes::from_static(b"f"),
+            value: Bytes::from_static(b"v"),
+        };
+        assert_eq!(cmd.execute(&backend, &mut Session::default()), wrong_type);
+
+        let cmd = HGet {
+            key: Bytes::from_static(b"str"),
+            field: Bytes::from_static(b"f"),
+        };
+        assert_eq!(cmd.execute(&backend, &mut Session::default()), wrong_type);
+
+        let cmd = HGetAll {
+            key: Bytes::from_static(b"str"),
+            sort: false,
+        };
+        assert_eq!(cmd.execute(&backend, &mut Session::default()), wrong_type);
+    }
 }
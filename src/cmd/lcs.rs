@@ -0,0 +1,266 @@
+use super::{
+    bulk_arg, extract_args, int_arg, syntax_error, validate_command, CommandExecutor, Lcs,
+};
+use crate::{cmd::CommandError, Backend, BulkString, RespArray, RespFrame, RespMap, Session};
+use bytes::Bytes;
+
+/// a common run of bytes, as the inclusive ranges it spans in each string
+type Match = ((usize, usize), (usize, usize));
+
+impl CommandExecutor for Lcs {
+    fn execute(self, backend: &Backend, _session: &mut Session) -> RespFrame {
+        let (a, b) = match (backend.get(&self.key1), backend.get(&self.key2)) {
+            (Ok(a), Ok(b)) => (a.unwrap_or_default(), b.unwrap_or_default()),
+            _ => {
+                return CommandError::Other(
+                    "The specified keys must contain string values".to_string(),
+                )
+                .into()
+            }
+        };
+        // the table takes a u32 per pair of positions, redis caps it like a bulk string
+        let table_size = (a.len() + 1)
+            .checked_mul(b.len() + 1)
+            .and_then(|n| n.checked_mul(size_of::<u32>()));
+        if table_size.is_none_or(|n| n > backend.limits().max_bulk_len) {
+            return CommandError::Other(
+                "Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len"
+                    .to_string(),
+            )
+            .into();
+        }
+
+        let (lcs, matches) = lcs(&a, &b, self.min_match_len);
+        if self.len {
+            return (lcs.len() as i64).into();
+        }
+        if !self.idx {
+            return RespFrame::BulkString(lcs.into());
+        }
+
+        let matches = matches
+            .into_iter()
+            .map(|((a_start, a_end), (b_start, b_end))| {
+                let mut item = vec![
+                    RespArray::new([(a_start as i64).into(), (a_end as i64).into()]).into(),
+                    RespArray::new([(b_start as i64).into(), (b_end as i64).into()]).into(),
+                ];
+                if self.with_match_len {
+                    item.push(((a_end - a_start + 1) as i64).into());
+                }
+                RespArray::new(item).into()
+            })
+            .collect::<Vec<RespFrame>>();
+        let mut map = RespMap::with_capacity(2);
+        map.push(BulkString::from("matches"), RespArray::new(matches));
+        map.push(BulkString::from("len"), lcs.len() as i64);
+        map.into()
+    }
+}
+
+impl TryFrom<RespArray> for Lcs {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lcs"])?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut lcs = Lcs {
+            key1: bulk_arg(args.next())?,
+            key2: bulk_arg(args.next())?,
+            len: false,
+            idx: false,
+            min_match_len: 0,
+            with_match_len: false,
+        };
+        while let Some(arg) = args.next() {
+            match bulk_arg(Some(arg))?.to_ascii_lowercase().as_slice() {
+                b"len" => lcs.len = true,
+                b"idx" => lcs.idx = true,
+                b"withmatchlen" => lcs.with_match_len = true,
+                b"minmatchlen" => {
+                    let len = int_arg(&bulk_arg(args.next()).map_err(|_| syntax_error())?)?;
+                    lcs.min_match_len = len.max(0) as usize;
+                }
+                _ => return Err(syntax_error()),
+            }
+        }
+        if lcs.len && lcs.idx {
+            return Err(CommandError::Other(
+                "If you want both the length and indexes, please just use IDX.".to_string(),
+            ));
+        }
+        Ok(lcs)
+    }
+}
+
+/// the longest common subsequence with the runs it is made of, from the end of the strings to
+/// their start like redis lists them. Runs shorter than `min_match_len` are left out.
+fn lcs(a: &[u8], b: &[u8], min_match_len: usize) -> (Bytes, Vec<Match>) {
+    // table[i][j] is the length of the LCS of a[..i] and b[..j]
+    let width = b.len() + 1;
+    let mut table = vec![0u32; (a.len() + 1) * width];
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            table[i * width + j] = if a[i - 1] == b[j - 1] {
+                table[(i - 1) * width + j - 1] + 1
+            } else {
+                table[(i - 1) * width + j].max(table[i * width + j - 1])
+            };
+        }
+    }
+
+    let mut result = vec![0; table[a.len() * width + b.len()] as usize];
+    let mut matches = Vec::new();
+    let mut current: Option<Match> = None;
+    let (mut i, mut j, mut idx) = (a.len(), b.len(), result.len());
+    while i > 0 && j > 0 {
+        let mut emit = false;
+        if a[i - 1] == b[j - 1] {
+            result[idx - 1] = a[i - 1];
+            match &mut current {
+                None => current = Some(((i - 1, i - 1), (j - 1, j - 1))),
+                // contiguous with the run so far, extend it backward
+                Some(((a_start, _), (b_start, _))) if *a_start == i && *b_start == j => {
+                    *a_start -= 1;
+                    *b_start -= 1;
+                }
+                Some(_) => emit = true,
+            }
+            // stop at the start of either string
+            emit |=
+                current.is_some_and(|((a_start, _), (b_start, _))| a_start == 0 || b_start == 0);
+            idx -= 1;
+            i -= 1;
+            j -= 1;
+        } else {
+            if table[(i - 1) * width + j] > table[i * width + j - 1] {
+                i -= 1;
+            } else {
+                j -= 1;
+            }
+            emit = current.is_some();
+        }
+        if emit {
+            if let Some(m @ ((a_start, a_end), _)) = current.take() {
+                if a_end - a_start + 1 >= min_match_len {
+                    matches.push(m);
+                }
+            }
+        }
+    }
+    (result.into(), matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::test_util::{err, exec};
+
+    fn range(start: i64, end: i64) -> RespFrame {
+        RespArray::new([start.into(), end.into()]).into()
+    }
+
+    #[test]
+    fn test_lcs() {
+        assert_eq!(lcs(b"ohmytext", b"mynewtext", 0).0, "mytext");
+        assert_eq!(lcs(b"", b"abc", 0), (Bytes::new(), vec![]));
+        assert_eq!(
+            lcs(b"ohmytext", b"mynewtext", 0).1,
+            vec![((4, 7), (5, 8)), ((2, 3), (0, 1))]
+        );
+        assert_eq!(lcs(b"ohmytext", b"mynewtext", 4).1, vec![((4, 7), (5, 8))]);
+        assert_eq!(lcs(b"abc", b"abc", 0).1, vec![((0, 2), (0, 2))]);
+    }
+
+    #[test]
+    fn test_lcs_command() {
+        let backend = Backend::new();
+        backend.set("key1".into(), "ohmytext".into());
+        backend.set("key2".into(), "mynewtext".into());
+
+        assert_eq!(
+            exec(&backend, &["lcs", "key1", "key2"]),
+            BulkString::from("mytext").into()
+        );
+        assert_eq!(
+            exec(&backend, &["LCS", "key1", "key2", "LEN"]),
+            RespFrame::Integer(6)
+        );
+        assert_eq!(
+            exec(&backend, &["lcs", "key1", "missing"]),
+            BulkString::from("").into()
+        );
+
+        let mut expected = RespMap::new();
+        expected.push(
+            BulkString::from("matches"),
+            RespArray::new([
+                RespArray::new([range(4, 7), range(5, 8)]).into(),
+                RespArray::new([range(2, 3), range(0, 1)]).into(),
+            ]),
+        );
+        expected.push(BulkString::from("len"), 6);
+        assert_eq!(
+            exec(&backend, &["lcs", "key1", "key2", "idx"]),
+            expected.into()
+        );
+
+        let mut expected = RespMap::new();
+        expected.push(
+            BulkString::from("matches"),
+            RespArray::new([RespArray::new([range(4, 7), range(5, 8), 4.into()]).into()]),
+        );
+        expected.push(BulkString::from("len"), 6);
+        assert_eq!(
+            exec(
+                &backend,
+                &[
+                    "lcs",
+                    "key1",
+                    "key2",
+                    "idx",
+                    "minmatchlen",
+                    "4",
+                    "withmatchlen"
+                ]
+            ),
+            expected.into()
+        );
+    }
+
+    #[test]
+    fn test_lcs_errors() {
+        let backend = Backend::with_limits(crate::RespLimits {
+            max_bulk_len: 64,
+            ..Default::default()
+        });
+        backend.set("a".into(), "abc".into());
+        backend.set("long".into(), "0123456789".into());
+        backend.hset("h".into(), "f".into(), "v".into()).unwrap();
+
+        assert_eq!(
+            exec(&backend, &["lcs", "a", "a", "len", "idx"]),
+            err("If you want both the length and indexes, please just use IDX.")
+        );
+        assert_eq!(
+            exec(&backend, &["lcs", "a", "a", "foo"]),
+            syntax_error().into()
+        );
+        assert_eq!(
+            exec(&backend, &["lcs", "a", "a", "minmatchlen"]),
+            syntax_error().into()
+        );
+        assert_eq!(
+            exec(&backend, &["lcs", "a", "h"]),
+            err("The specified keys must contain string values")
+        );
+        assert_eq!(
+            exec(&backend, &["lcs", "long", "long"]),
+            err("Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len")
+        );
+        assert_eq!(
+            exec(&backend, &["lcs", "a", "a", "len"]),
+            RespFrame::Integer(3)
+        );
+    }
+}
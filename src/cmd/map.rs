@@ -1,40 +1,136 @@
-use super::{extract_args, validate_command, CommandExecutor, Echo, Set, RESP_OK};
+use super::{
+    bulk_arg, command_name, extract_args, float_arg, int_arg, invalid_expire_time, syntax_error,
+    validate_command, Append, CommandExecutor, Echo, GetDel, GetEx, GetRange, IncrBy, IncrByFloat,
+    Set, SetNx, SetRange, StrLen, RESP_OK,
+};
 use crate::{
     cmd::{CommandError, Get},
-    RespArray, RespFrame,
+    now_ms, ExpiryUpdate, RespArray, RespFrame, RespNull, SetCondition, SetOptions,
 };
+use bytes::Bytes;
 
 impl CommandExecutor for Get {
-    fn execute(self, backend: &crate::Backend) -> RespFrame {
-        backend
-            .get(&self.key)
-            .unwrap_or(RespFrame::Null(crate::RespNull))
+    fn execute(self, backend: &crate::Backend, _session: &mut crate::Session) -> RespFrame {
+        match backend.get(&self.key) {
+            Ok(value) => bulk_or_null(value),
+            Err(e) => CommandError::from(e).into(),
+        }
     }
 }
 
 impl CommandExecutor for Echo {
-    fn execute(self, _backend: &crate::Backend) -> RespFrame {
+    fn execute(self, _backend: &crate::Backend, _session: &mut crate::Session) -> RespFrame {
         RespFrame::BulkString(self.key.into())
     }
 }
 
 impl CommandExecutor for Set {
-    fn execute(self, backend: &crate::Backend) -> RespFrame {
-        backend.set(self.key, self.value);
-        RESP_OK.clone()
+    fn execute(self, backend: &crate::Backend, _session: &mut crate::Session) -> RespFrame {
+        match backend.set_with(self.key, self.value, self.options) {
+            // with GET the reply is the old value whether or not the new one was set
+            Ok((_, old)) if self.options.get => bulk_or_null(old),
+            Ok((true, _)) => RESP_OK.clone(),
+            Ok((false, _)) => RespFrame::Null(RespNull),
+            Err(e) => CommandError::from(e).into(),
+        }
+    }
+}
+
+impl CommandExecutor for SetNx {
+    fn execute(self, backend: &crate::Backend, _session: &mut crate::Session) -> RespFrame {
+        let options = SetOptions {
+            condition: SetCondition::IfMissing,
+            ..Default::default()
+        };
+        match backend.set_with(self.key, self.value, options) {
+            Ok((set, _)) => i64::from(set).into(),
+            Err(e) => CommandError::from(e).into(),
+        }
+    }
+}
+
+impl CommandExecutor for GetEx {
+    fn execute(self, backend: &crate::Backend, _session: &mut crate::Session) -> RespFrame {
+        match backend.getex(&self.key, self.expiry) {
+            Ok(value) => bulk_or_null(value),
+            Err(e) => CommandError::from(e).into(),
+        }
+    }
+}
+
+impl CommandExecutor for GetDel {
+    fn execute(self, backend: &crate::Backend, _session: &mut crate::Session) -> RespFrame {
+        match backend.getdel(self.key) {
+            Ok(value) => bulk_or_null(value),
+            Err(e) => CommandError::from(e).into(),
+        }
+    }
+}
+
+impl CommandExecutor for IncrBy {
+    fn execute(self, backend: &crate::Backend, _session: &mut crate::Session) -> RespFrame {
+        match backend.incr_by(self.key, self.delta) {
+            Ok(n) => n.into(),
+            Err(e) => CommandError::from(e).into(),
+        }
+    }
+}
+
+impl CommandExecutor for IncrByFloat {
+    fn execute(self, backend: &crate::Backend, _session: &mut crate::Session) -> RespFrame {
+        match backend.incr_by_float(self.key, self.delta) {
+            Ok(value) => RespFrame::BulkString(value.into()),
+            Err(e) => CommandError::from(e).into(),
+        }
+    }
+}
+
+impl CommandExecutor for Append {
+    fn execute(self, backend: &crate::Backend, _session: &mut crate::Session) -> RespFrame {
+        match backend.append(self.key, &self.value) {
+            Ok(len) => (len as i64).into(),
+            Err(e) => CommandError::from(e).into(),
+        }
+    }
+}
+
+impl CommandExecutor for StrLen {
+    fn execute(self, backend: &crate::Backend, _session: &mut crate::Session) -> RespFrame {
+        match backend.strlen(&self.key) {
+            Ok(len) => (len as i64).into(),
+            Err(e) => CommandError::from(e).into(),
+        }
+    }
+}
+
+impl CommandExecutor for GetRange {
+    fn execute(self, backend: &crate::Backend, _session: &mut crate::Session) -> RespFrame {
+        match backend.getrange(&self.key, self.start, self.end) {
+            Ok(value) => RespFrame::BulkString(value.into()),
+            Err(e) => CommandError::from(e).into(),
+        }
+    }
+}
+
+impl CommandExecutor for SetRange {
+    fn execute(self, backend: &crate::Backend, _session: &mut crate::Session) -> RespFrame {
+        match backend.setrange(self.key, self.offset, &self.value) {
+            Ok(len) => (len as i64).into(),
+            Err(e) => CommandError::from(e).into(),
+        }
     }
 }
 
 impl TryFrom<RespArray> for Echo {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["echo"], 1)?;
+        validate_command(&value, &["echo"])?;
 
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => {
                 let key = match key.0 {
-                    Some(k) => String::from_utf8(k)?,
+                    Some(k) => k,
                     None => return Err(CommandError::InvalidArgument("Invalid field".to_string())),
                 };
                 Ok(Echo { key })
@@ -47,13 +143,13 @@ impl TryFrom<RespArray> for Echo {
 impl TryFrom<RespArray> for Get {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["get"], 1)?;
+        validate_command(&value, &["get"])?;
 
         let mut args = extract_args(value, 1)?.into_iter();
         match args.next() {
             Some(RespFrame::BulkString(key)) => {
                 let key = match key.0 {
-                    Some(k) => String::from_utf8(k)?,
+                    Some(k) => k,
                     None => return Err(CommandError::InvalidArgument("Invalid field".to_string())),
                 };
                 Ok(Get { key })
@@ -66,30 +162,259 @@ impl TryFrom<RespArray> for Get {
 impl TryFrom<RespArray> for Set {
     type Error = CommandError;
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["set"], 2)?;
+        let name = command_name(&value)?;
+        let name = match name.as_str() {
+            "set" => "set",
+            "getset" => "getset",
+            "setex" => "setex",
+            "psetex" => "psetex",
+            _ => return Err(CommandError::InvalidCommand(name)),
+        };
+        validate_command(&value, &[name])?;
 
         let mut args = extract_args(value, 1)?.into_iter();
-        match (args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(value)) => {
-                let key = match key.0 {
-                    Some(k) => String::from_utf8(k)?,
-                    None => return Err(CommandError::InvalidArgument("Invalid key".to_string())),
-                };
+        let key = bulk_arg(args.next())?;
+        let mut options = SetOptions::default();
+        let value = match name {
+            "getset" => {
+                options.get = true;
+                bulk_arg(args.next())?
+            }
+            "setex" | "psetex" => {
+                let unit = if name == "setex" { b"ex" } else { b"px" };
+                options.expiry = ExpiryUpdate::At(expire_time_arg(name, unit, args.next())?);
+                bulk_arg(args.next())?
+            }
+            _ => {
+                let value = bulk_arg(args.next())?;
+                parse_set_options(&mut options, args)?;
+                value
+            }
+        };
+        Ok(Set {
+            key,
+            value,
+            options,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SetNx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["setnx"])?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(SetNx {
+            key: bulk_arg(args.next())?,
+            value: bulk_arg(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for GetEx {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getex"])?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = bulk_arg(args.next())?;
+        let mut expiry = None;
+        while let Some(arg) = args.next() {
+            let arg = bulk_arg(Some(arg))?.to_ascii_lowercase();
+            expiry = match arg.as_slice() {
+                b"persist" if expiry.is_none() => Some(ExpiryUpdate::Clear),
+                b"ex" | b"px" | b"exat" | b"pxat" if expiry.is_none() => Some(ExpiryUpdate::At(
+                    expire_time_arg("getex", &arg, args.next())?,
+                )),
+                _ => return Err(syntax_error()),
+            };
+        }
+        Ok(GetEx {
+            key,
+            expiry: expiry.unwrap_or(ExpiryUpdate::Keep),
+        })
+    }
+}
+
+impl TryFrom<RespArray> for IncrBy {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let name = command_name(&value)?;
+        let name = match name.as_str() {
+            "incr" => "incr",
+            "decr" => "decr",
+            "incrby" => "incrby",
+            "decrby" => "decrby",
+            _ => return Err(CommandError::InvalidCommand(name)),
+        };
+        validate_command(&value, &[name])?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = bulk_arg(args.next())?;
+        let delta = match name {
+            "incr" => 1,
+            "decr" => -1,
+            "incrby" => int_arg(&bulk_arg(args.next())?)?,
+            _ => int_arg(&bulk_arg(args.next())?)?
+                .checked_neg()
+                .ok_or_else(|| CommandError::Other("decrement would overflow".to_string()))?,
+        };
+        Ok(IncrBy { key, delta })
+    }
+}
+
+impl TryFrom<RespArray> for IncrByFloat {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["incrbyfloat"])?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = bulk_arg(args.next())?;
+        let delta = float_arg(&bulk_arg(args.next())?)?;
+        Ok(IncrByFloat { key, delta })
+    }
+}
+
+impl TryFrom<RespArray> for GetDel {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getdel"])?;
+
+        let key = bulk_arg(extract_args(value, 1)?.into_iter().next())?;
+        Ok(GetDel { key })
+    }
+}
+
+impl TryFrom<RespArray> for Append {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["append"])?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Append {
+            key: bulk_arg(args.next())?,
+            value: bulk_arg(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for StrLen {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["strlen"])?;
 
-                Ok(Set { key, value })
+        let key = bulk_arg(extract_args(value, 1)?.into_iter().next())?;
+        Ok(StrLen { key })
+    }
+}
+
+impl TryFrom<RespArray> for GetRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let name = command_name(&value)?;
+        let name = match name.as_str() {
+            "getrange" => "getrange",
+            "substr" => "substr",
+            _ => return Err(CommandError::InvalidCommand(name)),
+        };
+        validate_command(&value, &[name])?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(GetRange {
+            key: bulk_arg(args.next())?,
+            start: int_arg(&bulk_arg(args.next())?)?,
+            end: int_arg(&bulk_arg(args.next())?)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SetRange {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["setrange"])?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = bulk_arg(args.next())?;
+        let offset = usize::try_from(int_arg(&bulk_arg(args.next())?)?)
+            .map_err(|_| CommandError::Other("offset is out of range".to_string()))?;
+        Ok(SetRange {
+            key,
+            offset,
+            value: bulk_arg(args.next())?,
+        })
+    }
+}
+
+/// the NX, XX, GET, EX, PX, EXAT, PXAT and KEEPTTL options of SET
+fn parse_set_options(
+    options: &mut SetOptions,
+    mut args: impl Iterator<Item = RespFrame>,
+) -> Result<(), CommandError> {
+    let mut has_expiry = false;
+    while let Some(arg) = args.next() {
+        let arg = bulk_arg(Some(arg))?.to_ascii_lowercase();
+        match arg.as_slice() {
+            b"nx" if options.condition != SetCondition::IfExists => {
+                options.condition = SetCondition::IfMissing
+            }
+            b"xx" if options.condition != SetCondition::IfMissing => {
+                options.condition = SetCondition::IfExists
+            }
+            b"get" => options.get = true,
+            b"keepttl" if !has_expiry => {
+                options.expiry = ExpiryUpdate::Keep;
+                has_expiry = true;
             }
-            _ => Err(CommandError::InvalidArgument(
-                "Invalid key or value".to_string(),
-            )),
+            b"ex" | b"px" | b"exat" | b"pxat" if !has_expiry => {
+                options.expiry = ExpiryUpdate::At(expire_time_arg("set", &arg, args.next())?);
+                has_expiry = true;
+            }
+            _ => return Err(syntax_error()),
         }
     }
+    Ok(())
+}
+
+/// the unix time in milliseconds an EX, PX, EXAT or PXAT option stands for. Like redis, the
+/// time has to be positive.
+fn expire_time_arg(name: &str, option: &[u8], arg: Option<RespFrame>) -> Result<i64, CommandError> {
+    let time = int_arg(&bulk_arg(arg).map_err(|_| syntax_error())?)?;
+    let (unit, absolute) = match option {
+        b"ex" => (1000, false),
+        b"px" => (1, false),
+        b"exat" => (1000, true),
+        _ => (1, true),
+    };
+    if time <= 0 {
+        return Err(invalid_expire_time(name));
+    }
+    let millis = time
+        .checked_mul(unit)
+        .ok_or_else(|| invalid_expire_time(name))?;
+    match absolute {
+        true => Ok(millis),
+        false => now_ms()
+            .checked_add(millis)
+            .ok_or_else(|| invalid_expire_time(name)),
+    }
+}
+
+fn bulk_or_null(value: Option<Bytes>) -> RespFrame {
+    match value {
+        Some(value) => RespFrame::BulkString(value.into()),
+        None => RespFrame::Null(RespNull),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Backend, RespDecode};
+    use crate::cmd::test_util::{err, exec};
+    use crate::Session;
+    use crate::{Backend, BulkString, RespDecode, RespEncode, SimpleString};
     use anyhow::Result;
+    use bytes::Bytes;
     use bytes::BytesMut;
 
     #[test]
@@ -114,7 +439,7 @@ mod tests {
 
         let result: Set = frame.try_into()?;
         assert_eq!(result.key, "hello");
-        assert_eq!(result.value, RespFrame::BulkString(b"world".into()));
+        assert_eq!(result.value, "world");
 
         Ok(())
     }
@@ -123,18 +448,321 @@ mod tests {
     fn test_set_get_command() -> Result<()> {
         let backend = Backend::new();
         let cmd = Set {
-            key: "hello".to_string(),
-            value: RespFrame::BulkString(b"world".into()),
+            key: Bytes::from_static(b"hello"),
+            value: Bytes::from_static(b"world"),
+            options: SetOptions::default(),
         };
-        let result = cmd.execute(&backend);
+        let result = cmd.execute(&backend, &mut Session::default());
         assert_eq!(result, RESP_OK.clone());
 
         let cmd = Get {
-            key: "hello".to_string(),
+            key: Bytes::from_static(b"hello"),
         };
-        let result = cmd.execute(&backend);
+        let result = cmd.execute(&backend, &mut Session::default());
         assert_eq!(result, RespFrame::BulkString(b"world".into()));
 
         Ok(())
     }
+
+    #[test]
+    fn test_set_get_binary() -> Result<()> {
+        let backend = Backend::new();
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\nset\r\n$2\r\n\xff\x00\r\n$3\r\n\xc3\x28\n\r\n");
+        let cmd: Set = RespArray::decode(&mut buf)?.try_into()?;
+        cmd.execute(&backend, &mut Session::default());
+
+        buf.extend_from_slice(b"*2\r\n$3\r\nget\r\n$2\r\n\xff\x00\r\n");
+        let cmd: Get = RespArray::decode(&mut buf)?.try_into()?;
+        let result = cmd.execute(&backend, &mut Session::default());
+        assert_eq!(result.encode(), b"$3\r\n\xc3\x28\n\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_wrong_type() {
+        let backend = Backend::new();
+        backend.hset("hash".into(), "f".into(), "v".into()).unwrap();
+        let cmd = Get {
+            key: Bytes::from_static(b"hash"),
+        };
+        let result = cmd.execute(&backend, &mut Session::default());
+        assert_eq!(result, CommandError::WrongType.into());
+    }
+
+    fn bulk(s: &str) -> RespFrame {
+        BulkString::from(s).into()
+    }
+
+    #[test]
+    fn test_set_options() -> Result<()> {
+        let backend = Backend::new();
+        let null = RespFrame::Null(RespNull);
+
+        assert_eq!(exec(&backend, &["set", "k", "a", "XX"]), null);
+        assert_eq!(
+            exec(&backend, &["set", "k", "a", "nx", "ex", "60"]),
+            RESP_OK.clone()
+        );
+        assert_eq!(exec(&backend, &["set", "k", "b", "nx", "ex", "60"]), null);
+        assert_eq!(exec(&backend, &["ttl", "k"]), RespFrame::Integer(60));
+        assert_eq!(exec(&backend, &["set", "k", "b", "NX", "GET"]), bulk("a"));
+        assert_eq!(
+            exec(&backend, &["set", "k", "b", "xx", "get", "keepttl"]),
+            bulk("a")
+        );
+        assert_eq!(exec(&backend, &["ttl", "k"]), RespFrame::Integer(60));
+        assert_eq!(exec(&backend, &["set", "k", "c", "get"]), bulk("b"));
+        assert_eq!(exec(&backend, &["ttl", "k"]), RespFrame::Integer(-1));
+        assert_eq!(exec(&backend, &["set", "new", "c", "get"]), null);
+
+        let at = (now_ms() + 5000).to_string();
+        assert_eq!(
+            exec(&backend, &["set", "k", "d", "pxat", &at]),
+            RESP_OK.clone()
+        );
+        assert_eq!(
+            exec(&backend, &["pexpiretime", "k"]),
+            RespFrame::Integer(at.parse()?)
+        );
+        assert_eq!(
+            exec(&backend, &["set", "k", "d", "px", "2000"]),
+            RESP_OK.clone()
+        );
+        assert_eq!(exec(&backend, &["ttl", "k"]), RespFrame::Integer(2));
+        // an EXAT already passed leaves the key expired
+        assert_eq!(
+            exec(&backend, &["set", "k", "d", "exat", "1"]),
+            RESP_OK.clone()
+        );
+        assert_eq!(exec(&backend, &["get", "k"]), null);
+
+        exec(&backend, &["hset", "h", "f", "v"]);
+        assert_eq!(
+            exec(&backend, &["set", "h", "v", "get"]),
+            CommandError::WrongType.into()
+        );
+        assert_eq!(
+            exec(&backend, &["type", "h"]),
+            SimpleString::new("hash").into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_option_errors() {
+        let backend = Backend::new();
+        let syntax_error: RespFrame = syntax_error().into();
+        for args in [
+            &["set", "k", "v", "nx", "xx"][..],
+            &["set", "k", "v", "ex", "10", "px", "10"],
+            &["set", "k", "v", "ex", "10", "keepttl"],
+            &["set", "k", "v", "ex"],
+            &["set", "k", "v", "foo"],
+            &["getex", "k", "persist", "ex", "10"],
+        ] {
+            assert_eq!(exec(&backend, args), syntax_error, "{:?}", args);
+        }
+        assert_eq!(
+            exec(&backend, &["set", "k", "v", "ex", "0"]),
+            invalid_expire_time("set").into()
+        );
+        assert_eq!(
+            exec(&backend, &["set", "k", "v", "ex", "9223372036854775807"]),
+            invalid_expire_time("set").into()
+        );
+        assert_eq!(
+            exec(&backend, &["set", "k", "v", "px", "ten"]),
+            CommandError::Other("value is not an integer or out of range".to_string()).into()
+        );
+        assert_eq!(
+            exec(&backend, &["setex", "k", "-1", "v"]),
+            invalid_expire_time("setex").into()
+        );
+        assert_eq!(exec(&backend, &["get", "k"]), RespFrame::Null(RespNull));
+    }
+
+    #[test]
+    fn test_set_companion_commands() {
+        let backend = Backend::new();
+        let null = RespFrame::Null(RespNull);
+
+        assert_eq!(exec(&backend, &["setnx", "k", "a"]), RespFrame::Integer(1));
+        assert_eq!(exec(&backend, &["setnx", "k", "b"]), RespFrame::Integer(0));
+        assert_eq!(exec(&backend, &["getset", "k", "b"]), bulk("a"));
+        assert_eq!(exec(&backend, &["getset", "new", "b"]), null);
+
+        assert_eq!(exec(&backend, &["setex", "k", "100", "c"]), RESP_OK.clone());
+        assert_eq!(exec(&backend, &["ttl", "k"]), RespFrame::Integer(100));
+        assert_eq!(
+            exec(&backend, &["psetex", "k", "20000", "d"]),
+            RESP_OK.clone()
+        );
+        assert_eq!(exec(&backend, &["ttl", "k"]), RespFrame::Integer(20));
+
+        assert_eq!(exec(&backend, &["getex", "k"]), bulk("d"));
+        assert_eq!(exec(&backend, &["ttl", "k"]), RespFrame::Integer(20));
+        assert_eq!(exec(&backend, &["getex", "k", "EX", "50"]), bulk("d"));
+        assert_eq!(exec(&backend, &["ttl", "k"]), RespFrame::Integer(50));
+        assert_eq!(exec(&backend, &["getex", "k", "persist"]), bulk("d"));
+        assert_eq!(exec(&backend, &["ttl", "k"]), RespFrame::Integer(-1));
+        assert_eq!(exec(&backend, &["getex", "missing", "px", "10"]), null);
+
+        assert_eq!(exec(&backend, &["getdel", "k"]), bulk("d"));
+        assert_eq!(exec(&backend, &["getdel", "k"]), null);
+        assert_eq!(
+            exec(&backend, &["type", "k"]),
+            SimpleString::new("none").into()
+        );
+    }
+
+    #[test]
+    fn test_counter_commands() {
+        let backend = Backend::new();
+
+        assert_eq!(exec(&backend, &["incr", "n"]), RespFrame::Integer(1));
+        assert_eq!(
+            exec(&backend, &["INCRBY", "n", "10"]),
+            RespFrame::Integer(11)
+        );
+        assert_eq!(exec(&backend, &["decr", "n"]), RespFrame::Integer(10));
+        assert_eq!(
+            exec(&backend, &["decrby", "n", "-5"]),
+            RespFrame::Integer(15)
+        );
+        assert_eq!(exec(&backend, &["get", "n"]), bulk("15"));
+        assert_eq!(exec(&backend, &["object", "encoding", "n"]), bulk("int"));
+
+        assert_eq!(
+            exec(&backend, &["decrby", "n", "-9223372036854775808"]),
+            err("decrement would overflow")
+        );
+        assert_eq!(
+            exec(&backend, &["incrby", "n", "9223372036854775807"]),
+            err("increment or decrement would overflow")
+        );
+        assert_eq!(
+            exec(&backend, &["incrby", "n", "1.5"]),
+            err("value is not an integer or out of range")
+        );
+        exec(&backend, &["set", "s", "abc"]);
+        assert_eq!(
+            exec(&backend, &["incr", "s"]),
+            err("value is not an integer or out of range")
+        );
+        exec(&backend, &["hset", "h", "f", "1"]);
+        assert_eq!(
+            exec(&backend, &["incr", "h"]),
+            CommandError::WrongType.into()
+        );
+        assert_eq!(
+            exec(&backend, &["incrbyfloat", "h", "1"]),
+            CommandError::WrongType.into()
+        );
+    }
+
+    #[test]
+    fn test_incrbyfloat_command() {
+        let backend = Backend::new();
+
+        exec(&backend, &["set", "f", "10.50"]);
+        assert_eq!(exec(&backend, &["incrbyfloat", "f", "0.1"]), bulk("10.6"));
+        assert_eq!(exec(&backend, &["incrbyfloat", "f", "-5"]), bulk("5.6"));
+        assert_eq!(
+            exec(&backend, &["incrbyfloat", "f", "2.0e2"]),
+            bulk("205.6")
+        );
+        assert_eq!(exec(&backend, &["incrbyfloat", "n", "3"]), bulk("3"));
+        exec(&backend, &["set", "g", "0.2"]);
+        assert_eq!(exec(&backend, &["incrbyfloat", "g", "0.1"]), bulk("0.3"));
+        exec(&backend, &["set", "big", "1e10"]);
+        assert_eq!(
+            exec(&backend, &["incrbyfloat", "big", "0.000001"]),
+            bulk("10000000000.000001")
+        );
+        assert_eq!(exec(&backend, &["incr", "n"]), RespFrame::Integer(4));
+
+        assert_eq!(
+            exec(&backend, &["incrbyfloat", "f", "abc"]),
+            err("value is not a valid float")
+        );
+        assert_eq!(
+            exec(&backend, &["incrbyfloat", "f", "inf"]),
+            err("increment would produce NaN or Infinity")
+        );
+        exec(&backend, &["set", "s", "abc"]);
+        assert_eq!(
+            exec(&backend, &["incrbyfloat", "s", "1"]),
+            err("value is not a valid float")
+        );
+    }
+
+    #[test]
+    fn test_string_range_commands() {
+        let backend = Backend::new();
+
+        assert_eq!(
+            exec(&backend, &["append", "log", "line 1\n"]),
+            RespFrame::Integer(7)
+        );
+        assert_eq!(
+            exec(&backend, &["append", "log", "line 2\n"]),
+            RespFrame::Integer(14)
+        );
+        assert_eq!(exec(&backend, &["strlen", "log"]), RespFrame::Integer(14));
+        assert_eq!(
+            exec(&backend, &["strlen", "missing"]),
+            RespFrame::Integer(0)
+        );
+        assert_eq!(
+            exec(&backend, &["getrange", "log", "7", "-2"]),
+            bulk("line 2")
+        );
+        assert_eq!(exec(&backend, &["substr", "log", "0", "3"]), bulk("line"));
+        assert_eq!(
+            exec(&backend, &["getrange", "missing", "0", "-1"]),
+            bulk("")
+        );
+
+        assert_eq!(
+            exec(&backend, &["setrange", "log", "5", "9"]),
+            RespFrame::Integer(14)
+        );
+        assert_eq!(
+            exec(&backend, &["getrange", "log", "0", "6"]),
+            bulk("line 9\n")
+        );
+        assert_eq!(
+            exec(&backend, &["setrange", "new", "2", "x"]),
+            RespFrame::Integer(3)
+        );
+        assert_eq!(exec(&backend, &["get", "new"]), bulk("\0\0x"));
+
+        assert_eq!(
+            exec(&backend, &["setrange", "new", "-1", "x"]),
+            err("offset is out of range")
+        );
+        assert_eq!(
+            exec(&backend, &["setrange", "new", "536870911", "xx"]),
+            err("string exceeds maximum allowed size (proto-max-bulk-len)")
+        );
+        assert_eq!(
+            exec(&backend, &["getrange", "log", "a", "1"]),
+            err("value is not an integer or out of range")
+        );
+        exec(&backend, &["hset", "h", "f", "v"]);
+        for args in [
+            &["append", "h", "x"][..],
+            &["strlen", "h"],
+            &["getrange", "h", "0", "1"],
+            &["setrange", "h", "0", "x"],
+        ] {
+            assert_eq!(
+                exec(&backend, args),
+                CommandError::WrongType.into(),
+                "{:?}",
+                args
+            );
+        }
+    }
 }
@@ -1,21 +1,59 @@
+mod client;
+mod command;
+mod connection;
+mod generic;
 mod hmap;
+mod lcs;
 mod map;
+mod server;
+mod table;
 
-use crate::{Backend, RespArray, RespError, RespFrame, SimpleString};
+use crate::{
+    parse_float, parse_int, Backend, BulkString, ExpireOptions, ExpiryUpdate, RespArray, RespError,
+    RespFrame, RespVersion, Session, SetOptions, ShutdownMode, SimpleError, SimpleString,
+    StringError, WrongType,
+};
+use bytes::Bytes;
 use enum_dispatch::enum_dispatch;
 use lazy_static::lazy_static;
 use thiserror::Error;
 
+pub use table::{lookup_command, CommandSpec, COMMAND_TABLE};
+
 lazy_static! {
     static ref RESP_OK: RespFrame = SimpleString::new("OK").into();
 }
 
+/// max bytes of the arguments echoed back in an unknown command error
+const UNKNOWN_COMMAND_ARGS_LEN: usize = 128;
+
 #[derive(Error, Debug)]
 pub enum CommandError {
     #[error("Invalid command: {0}")]
     InvalidCommand(String),
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
+    #[error("unknown command '{name}', with args beginning with: {}", format_args(.args))]
+    UnknownCommand { name: String, args: Vec<String> },
+    #[error("unknown subcommand '{sub}'. Try {} HELP.", .command.to_uppercase())]
+    UnknownSubcommand { command: String, sub: String },
+    #[error("wrong number of arguments for '{0}' command")]
+    WrongArity(String),
+    #[error("Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("{0}")]
+    NoProto(String),
+    #[error("No matching script. Please use EVAL.")]
+    NoScript,
+    #[error("command not allowed when used memory > 'maxmemory'.")]
+    OutOfMemory,
+    #[error("Transaction discarded because of previous errors.")]
+    ExecAbort,
+    #[error("invalid username-password pair or user is disabled.")]
+    WrongPass,
+    /// an error replied with the message as is
+    #[error("{0}")]
+    Other(String),
 
     #[error("{0}")]
     RespError(#[from] RespError),
@@ -23,62 +61,325 @@ pub enum CommandError {
     Utf8Error(#[from] std::string::FromUtf8Error),
 }
 
+impl CommandError {
+    /// the error code redis clients match on, it is the first word of the reply
+    pub fn code(&self) -> &'static str {
+        match self {
+            CommandError::WrongType => "WRONGTYPE",
+            CommandError::NoProto(_) => "NOPROTO",
+            CommandError::NoScript => "NOSCRIPT",
+            CommandError::OutOfMemory => "OOM",
+            CommandError::ExecAbort => "EXECABORT",
+            CommandError::WrongPass => "WRONGPASS",
+            _ => "ERR",
+        }
+    }
+}
+
+impl From<WrongType> for CommandError {
+    fn from(_: WrongType) -> Self {
+        CommandError::WrongType
+    }
+}
+
+impl From<StringError> for CommandError {
+    fn from(e: StringError) -> Self {
+        match e {
+            StringError::WrongType(_) => CommandError::WrongType,
+            e => CommandError::Other(e.to_string()),
+        }
+    }
+}
+
+impl From<CommandError> for SimpleError {
+    fn from(e: CommandError) -> Self {
+        // a simple error can't carry CR or LF, they would end the frame early
+        let msg = format!("{} {}", e.code(), e).replace(['\r', '\n'], " ");
+        SimpleError::new(msg)
+    }
+}
+
+impl From<CommandError> for RespFrame {
+    fn from(e: CommandError) -> Self {
+        SimpleError::from(e).into()
+    }
+}
+
+/// quote the arguments the way redis does, stopping once they exceed the length limit
+fn format_args(args: &[String]) -> String {
+    let mut ret = String::new();
+    for arg in args {
+        if ret.len() >= UNKNOWN_COMMAND_ARGS_LEN {
+            break;
+        }
+        let limit = UNKNOWN_COMMAND_ARGS_LEN - ret.len();
+        let arg = &arg[..arg.floor_char_boundary(limit)];
+        ret.push_str(&format!("'{}' ", arg));
+    }
+    ret
+}
+
 #[enum_dispatch]
 pub trait CommandExecutor {
-    fn execute(self, backend: &Backend) -> RespFrame;
+    fn execute(self, backend: &Backend, session: &mut Session) -> RespFrame;
 }
 
 #[enum_dispatch(CommandExecutor)]
 #[derive(Debug)]
 pub enum Command {
+    Append(Append),
+    Client(ClientCommand),
+    Command(CommandIntrospect),
     Echo(Echo),
+    Expire(Expire),
+    Hello(Hello),
     Get(Get),
+    GetDel(GetDel),
+    GetEx(GetEx),
+    GetRange(GetRange),
     Set(Set),
+    SetNx(SetNx),
+    SetRange(SetRange),
     HGet(HGet),
     HSet(HSet),
     HGetAll(HGetAll),
+    IncrBy(IncrBy),
+    IncrByFloat(IncrByFloat),
+    Lcs(Lcs),
+    Object(Object),
+    Persist(Persist),
+    Shutdown(Shutdown),
+    StrLen(StrLen),
+    Ttl(Ttl),
+    Type(Type),
+}
 
-    // unrecognized command
-    Unrecognized(Unrecognized),
+/// COMMAND and its subcommands
+#[derive(Debug)]
+pub struct CommandIntrospect {
+    sub: IntrospectSub,
+}
+
+#[derive(Debug)]
+enum IntrospectSub {
+    List,
+    Count,
+    Info(Vec<String>),
+    Docs(Vec<String>),
+    GetKeys(Vec<RespFrame>),
 }
 
 #[derive(Debug)]
 pub struct Echo {
-    key: String,
+    key: Bytes,
+}
+
+/// CLIENT and its subcommands
+#[derive(Debug)]
+pub struct ClientCommand {
+    sub: ClientSub,
+}
+
+#[derive(Debug)]
+enum ClientSub {
+    Id,
+    SetName(String),
+    GetName,
+    /// `None` lists every client
+    List {
+        ids: Option<Vec<u64>>,
+        /// TYPE names a kind of client the server never has, nothing matches
+        other_type: bool,
+    },
+    Info,
+    Kill(ClientKill),
+    SetInfo {
+        attr: &'static str,
+        value: String,
+    },
+}
+
+#[derive(Debug)]
+enum ClientKill {
+    /// the legacy form, kills the client connected from this address
+    Addr(String),
+    Filter {
+        id: Option<u64>,
+        /// TYPE names a kind of client the server never has, nothing matches
+        other_type: bool,
+        addr: Option<String>,
+        laddr: Option<String>,
+        skipme: bool,
+        maxage: Option<u64>,
+    },
+}
+
+/// HELLO [protover [AUTH username password] [SETNAME clientname]]
+#[derive(Debug)]
+pub struct Hello {
+    protover: Option<RespVersion>,
+    auth: Option<(String, String)>,
+    setname: Option<String>,
+}
+
+/// SHUTDOWN [NOSAVE|SAVE] [NOW] [FORCE] [ABORT]
+#[derive(Debug)]
+pub struct Shutdown {
+    mode: ShutdownMode,
+    abort: bool,
 }
 
 #[derive(Debug)]
 pub struct Get {
-    key: String,
+    key: Bytes,
 }
 
+/// SET key value [NX | XX] [GET] [EX seconds | PX milliseconds | EXAT unix-time-seconds |
+/// PXAT unix-time-milliseconds | KEEPTTL], GETSET, SETEX and PSETEX are parsed into it too
 #[derive(Debug)]
 pub struct Set {
-    key: String,
-    value: RespFrame,
+    key: Bytes,
+    value: Bytes,
+    options: SetOptions,
+}
+
+/// SETNX key value
+#[derive(Debug)]
+pub struct SetNx {
+    key: Bytes,
+    value: Bytes,
+}
+
+/// GETEX key [EX seconds | PX milliseconds | EXAT unix-time-seconds |
+/// PXAT unix-time-milliseconds | PERSIST]
+#[derive(Debug)]
+pub struct GetEx {
+    key: Bytes,
+    expiry: ExpiryUpdate,
+}
+
+/// INCR, DECR, INCRBY and DECRBY
+#[derive(Debug)]
+pub struct IncrBy {
+    key: Bytes,
+    delta: i64,
+}
+
+/// INCRBYFLOAT key increment
+#[derive(Debug)]
+pub struct IncrByFloat {
+    key: Bytes,
+    delta: f64,
+}
+
+/// APPEND key value
+#[derive(Debug)]
+pub struct Append {
+    key: Bytes,
+    value: Bytes,
+}
+
+/// STRLEN key
+#[derive(Debug)]
+pub struct StrLen {
+    key: Bytes,
+}
+
+/// GETRANGE key start end, SUBSTR is its old name
+#[derive(Debug)]
+pub struct GetRange {
+    key: Bytes,
+    start: i64,
+    end: i64,
+}
+
+/// SETRANGE key offset value
+#[derive(Debug)]
+pub struct SetRange {
+    key: Bytes,
+    offset: usize,
+    value: Bytes,
+}
+
+/// LCS key1 key2 [LEN] [IDX] [MINMATCHLEN min-match-len] [WITHMATCHLEN]
+#[derive(Debug)]
+pub struct Lcs {
+    key1: Bytes,
+    key2: Bytes,
+    len: bool,
+    idx: bool,
+    min_match_len: usize,
+    with_match_len: bool,
+}
+
+/// GETDEL key
+#[derive(Debug)]
+pub struct GetDel {
+    key: Bytes,
 }
 
 #[derive(Debug)]
 pub struct HGet {
-    key: String,
-    field: String,
+    key: Bytes,
+    field: Bytes,
 }
 
 #[derive(Debug)]
 pub struct HSet {
-    key: String,
-    field: String,
-    value: RespFrame,
+    key: Bytes,
+    field: Bytes,
+    value: Bytes,
 }
 
 #[derive(Debug)]
 pub struct HGetAll {
-    key: String,
+    key: Bytes,
     sort: bool,
 }
 
+/// TYPE key
+#[derive(Debug)]
+pub struct Type {
+    key: Bytes,
+}
+
+/// EXPIRE, PEXPIRE, EXPIREAT and PEXPIREAT key time [NX | XX | GT | LT]
+#[derive(Debug)]
+pub struct Expire {
+    name: &'static str,
+    key: Bytes,
+    /// unix time in milliseconds for the AT forms, milliseconds from now otherwise
+    millis: i64,
+    absolute: bool,
+    options: ExpireOptions,
+}
+
+/// TTL, PTTL, EXPIRETIME and PEXPIRETIME key
 #[derive(Debug)]
-pub struct Unrecognized;
+pub struct Ttl {
+    key: Bytes,
+    /// reply in milliseconds rather than seconds
+    millis: bool,
+    /// reply with the unix time of the deadline rather than the time left
+    absolute: bool,
+}
+
+/// PERSIST key
+#[derive(Debug)]
+pub struct Persist {
+    key: Bytes,
+}
+
+/// OBJECT and its subcommands
+#[derive(Debug)]
+pub struct Object {
+    sub: ObjectSub,
+}
+
+#[derive(Debug)]
+enum ObjectSub {
+    Encoding(Bytes),
+}
 
 impl TryFrom<RespFrame> for Command {
     type Error = CommandError;
@@ -95,47 +396,66 @@ impl TryFrom<RespFrame> for Command {
 impl TryFrom<RespArray> for Command {
     type Error = CommandError;
     fn try_from(v: RespArray) -> Result<Self, Self::Error> {
-        match &v.0 {
-            Some(vec) => match vec.first() {
-                Some(RespFrame::BulkString(ref cmd)) => match cmd.as_ref() {
-                    b"get" => Ok(Get::try_from(v)?.into()),
-                    b"echo" => Ok(Echo::try_from(v)?.into()),
-                    b"set" => Ok(Set::try_from(v)?.into()),
-                    b"hget" => Ok(HGet::try_from(v)?.into()),
-                    b"hset" => Ok(HSet::try_from(v)?.into()),
-                    b"hgetall" => Ok(HGetAll::try_from(v)?.into()),
-                    _ => Ok(Unrecognized.into()),
-                },
-                _ => Err(CommandError::InvalidCommand(
+        let vec = match &v.0 {
+            Some(vec) => vec,
+            None => {
+                return Err(CommandError::InvalidCommand(
                     "Command must have a BulkString as the first argument".to_string(),
-                )),
-            },
-            _ => Err(CommandError::InvalidCommand(
-                "Command must have a BulkString as the first argument".to_string(),
-            )),
+                ))
+            }
+        };
+        let name = match vec.first() {
+            Some(RespFrame::BulkString(cmd)) => String::from_utf8_lossy(cmd.as_ref()).to_string(),
+            _ => {
+                return Err(CommandError::InvalidCommand(
+                    "Command must have a BulkString as the first argument".to_string(),
+                ))
+            }
+        };
+
+        let Some(spec) = lookup_command(&name) else {
+            return Err(CommandError::UnknownCommand {
+                name,
+                args: vec[1..].iter().map(arg_to_string).collect(),
+            });
+        };
+
+        match vec.get(1) {
+            Some(sub) if !spec.subcommands.is_empty() => {
+                let sub = arg_to_string(sub);
+                match spec.subcommand(&sub) {
+                    Some(spec) => spec.parse(v),
+                    None => Err(CommandError::UnknownSubcommand { command: name, sub }),
+                }
+            }
+            _ => spec.parse(v),
         }
     }
 }
 
-impl CommandExecutor for Unrecognized {
-    fn execute(self, _: &Backend) -> RespFrame {
-        RESP_OK.clone()
+fn arg_to_string(arg: &RespFrame) -> String {
+    match arg {
+        RespFrame::BulkString(arg) => String::from_utf8_lossy(arg.as_ref()).to_string(),
+        arg => format!("{:?}", arg),
     }
 }
 
-fn validate_command(
-    value: &RespArray,
-    names: &[&'static str],
-    n_args: usize,
-) -> Result<(), CommandError> {
+/// check the arguments against the command table: `names` is the command followed by its
+/// subcommand if any
+fn validate_command(value: &RespArray, names: &[&'static str]) -> Result<(), CommandError> {
+    let full_name = names.join("|");
+    let mut spec = lookup_command(names[0]);
+    for sub in &names[1..] {
+        spec = spec.and_then(|spec| spec.subcommand(sub));
+    }
+    let spec = spec.ok_or_else(|| {
+        CommandError::InvalidCommand(format!("{} is not in the command table", full_name))
+    })?;
+
     match &value.0 {
         Some(vec) => {
-            if vec.len() != n_args + names.len() {
-                return Err(CommandError::InvalidArgument(format!(
-                    "{} command must have exactly {} argument",
-                    names.join(" "),
-                    n_args
-                )));
+            if !spec.check_arity(vec.len()) {
+                return Err(CommandError::WrongArity(full_name));
             }
 
             for (i, name) in names.iter().enumerate() {
@@ -163,6 +483,47 @@ fn validate_command(
     }
 }
 
+/// the bytes of a bulk string argument
+fn bulk_arg(arg: Option<RespFrame>) -> Result<Bytes, CommandError> {
+    match arg {
+        Some(RespFrame::BulkString(BulkString(Some(arg)))) => Ok(arg),
+        _ => Err(CommandError::InvalidArgument(
+            "Invalid argument".to_string(),
+        )),
+    }
+}
+
+/// parse an integer argument as strictly as redis does, `+1` or `01` are not integers
+fn int_arg(arg: &[u8]) -> Result<i64, CommandError> {
+    parse_int(arg)
+        .ok_or_else(|| CommandError::Other("value is not an integer or out of range".to_string()))
+}
+
+/// parse a float argument, NaN is not a float
+fn float_arg(arg: &[u8]) -> Result<f64, CommandError> {
+    parse_float(arg).ok_or_else(|| CommandError::Other("value is not a valid float".to_string()))
+}
+
+/// the lowercase name of the command, for parsers shared by several commands
+fn command_name(value: &RespArray) -> Result<String, CommandError> {
+    match value.as_ref().and_then(|vec| vec.first()) {
+        Some(RespFrame::BulkString(name)) => {
+            Ok(String::from_utf8_lossy(name.as_ref()).to_lowercase())
+        }
+        _ => Err(CommandError::InvalidCommand(
+            "Command must have a BulkString as the first argument".to_string(),
+        )),
+    }
+}
+
+fn invalid_expire_time(name: &str) -> CommandError {
+    CommandError::Other(format!("invalid expire time in '{}' command", name))
+}
+
+fn syntax_error() -> CommandError {
+    CommandError::Other("syntax error".to_string())
+}
+
 fn extract_args(value: RespArray, start: usize) -> Result<Vec<RespFrame>, CommandError> {
     match value.0 {
         Some(vec) => Ok(vec.into_iter().skip(start).collect::<Vec<RespFrame>>()),
@@ -172,10 +533,43 @@ fn extract_args(value: RespArray, start: usize) -> Result<Vec<RespFrame>, Comman
     }
 }
 
+/// helpers shared by the tests of the commands
+#[cfg(test)]
+mod test_util {
+    use super::{Command, CommandError, CommandExecutor};
+    use crate::{Backend, BulkString, Client, RespArray, RespFrame, Session};
+
+    /// run a command given as its arguments, parse errors become the error reply
+    pub(super) fn exec(backend: &Backend, args: &[&str]) -> RespFrame {
+        exec_in(backend, &mut Session::default(), args)
+    }
+
+    /// run a command on behalf of the session
+    pub(super) fn exec_in(backend: &Backend, session: &mut Session, args: &[&str]) -> RespFrame {
+        let args = args.iter().map(|arg| BulkString::from(*arg).into());
+        match Command::try_from(RespArray::new(args.collect::<Vec<_>>())) {
+            Ok(cmd) => cmd.execute(backend, session),
+            Err(e) => e.into(),
+        }
+    }
+
+    /// a session for a client connected from `addr`, registered like a connection would be
+    pub(super) fn connect(backend: &Backend, addr: &str) -> Session {
+        let session = Session::with_client(Client::new(addr, "127.0.0.1:6379"));
+        backend.register_client(session.client().clone());
+        session
+    }
+
+    /// the reply of an error with the given message
+    pub(super) fn err(msg: &str) -> RespFrame {
+        CommandError::Other(msg.to_string()).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{RespDecode, RespNull};
+    use crate::{RespDecode, RespNull, Session};
     use anyhow::Result;
     use bytes::BytesMut;
 
@@ -190,9 +584,67 @@ mod tests {
 
         let backend = Backend::new();
 
-        let ret = cmd.execute(&backend);
+        let ret = cmd.execute(&backend, &mut Session::default());
         assert_eq!(ret, RespFrame::Null(RespNull));
 
         Ok(())
     }
+
+    #[test]
+    fn test_unknown_command_error() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\nfoo\r\n$1\r\na\r\n$1\r\nb\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let err = Command::try_from(frame).unwrap_err();
+        assert_eq!(
+            RespFrame::from(err),
+            SimpleError::new("ERR unknown command 'foo', with args beginning with: 'a' 'b' ")
+                .into()
+        );
+
+        // the limit is in bytes, a multi-byte character is never split
+        let err = CommandError::UnknownCommand {
+            name: "foo".to_string(),
+            args: vec!["é".repeat(100), "b".to_string()],
+        };
+        let expected = format!(
+            "unknown command 'foo', with args beginning with: '{}' ",
+            "é".repeat(64)
+        );
+        assert_eq!(err.to_string(), expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrong_arity_error() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*1\r\n$3\r\nget\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let err = Command::try_from(frame).unwrap_err();
+        assert_eq!(
+            RespFrame::from(err),
+            SimpleError::new("ERR wrong number of arguments for 'get' command").into()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_code() {
+        let frame: RespFrame = CommandError::WrongType.into();
+        assert_eq!(
+            frame,
+            SimpleError::new("WRONGTYPE Operation against a key holding the wrong kind of value")
+                .into()
+        );
+
+        let frame: RespFrame = CommandError::NoProto("unsupported protocol version".into()).into();
+        assert_eq!(
+            frame,
+            SimpleError::new("NOPROTO unsupported protocol version").into()
+        );
+    }
 }
@@ -0,0 +1,84 @@
+use super::{
+    extract_args, syntax_error, validate_command, CommandError, CommandExecutor, Shutdown, RESP_OK,
+};
+use crate::{Backend, RespArray, RespFrame, Session, ShutdownMode};
+
+impl CommandExecutor for Shutdown {
+    fn execute(self, backend: &Backend, session: &mut Session) -> RespFrame {
+        if self.abort {
+            // shutdown never waits for replicas, so there is nothing left to abort
+            return CommandError::Other("No shutdown in progress.".to_string()).into();
+        }
+        // the connections close once their batch is replied, the save runs after that. Like redis,
+        // the client asking for it gets no reply, clients take one as the shutdown failing.
+        backend.shutdown(self.mode);
+        session.skip_reply();
+        session.client().kill();
+        RESP_OK.clone()
+    }
+}
+
+impl TryFrom<RespArray> for Shutdown {
+    type Error = CommandError;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["shutdown"])?;
+
+        let mut shutdown = Shutdown {
+            mode: ShutdownMode::default(),
+            abort: false,
+        };
+        let mut now = false;
+        for arg in extract_args(value, 1)? {
+            let RespFrame::BulkString(arg) = arg else {
+                return Err(syntax_error());
+            };
+            match arg.as_ref().to_ascii_lowercase().as_slice() {
+                b"nosave" if shutdown.mode.save.is_none() => shutdown.mode.save = Some(false),
+                b"save" if shutdown.mode.save.is_none() => shutdown.mode.save = Some(true),
+                // there are no replicas to wait for, NOW changes nothing
+                b"now" => now = true,
+                b"force" => shutdown.mode.force = true,
+                b"abort" => shutdown.abort = true,
+                _ => return Err(syntax_error()),
+            }
+        }
+        if shutdown.abort && (shutdown.mode != ShutdownMode::default() || now) {
+            return Err(syntax_error());
+        }
+        Ok(shutdown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::test_util::{err, exec, exec_in};
+
+    #[test]
+    fn test_shutdown() {
+        let backend = Backend::new();
+        let mut session = Session::new();
+        exec_in(&backend, &mut session, &["SHUTDOWN", "nosave", "now"]);
+        assert!(backend.is_shutting_down());
+        assert!(session.take_skip_reply());
+        assert!(session.client().is_killed());
+    }
+
+    #[test]
+    fn test_shutdown_errors() {
+        let backend = Backend::new();
+        assert_eq!(
+            exec(&backend, &["shutdown", "save", "nosave"]),
+            err("syntax error")
+        );
+        assert_eq!(
+            exec(&backend, &["shutdown", "abort", "force"]),
+            err("syntax error")
+        );
+        assert_eq!(
+            exec(&backend, &["shutdown", "ABORT"]),
+            err("No shutdown in progress.")
+        );
+        assert!(!backend.is_shutting_down());
+    }
+}
@@ -0,0 +1,875 @@
+use super::{
+    Append, ClientCommand, Command, CommandError, CommandIntrospect, Echo, Expire, Get, GetDel,
+    GetEx, GetRange, HGet, HGetAll, HSet, Hello, IncrBy, IncrByFloat, Lcs, Object, Persist, Set,
+    SetNx, SetRange, Shutdown, StrLen, Ttl, Type,
+};
+use crate::RespArray;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+
+type ParseFn = fn(RespArray) -> Result<Command, CommandError>;
+
+/// static description of a command, the same data redis keeps in its command table
+#[derive(Debug)]
+pub struct CommandSpec {
+    /// lowercase name, subcommands are named `container|sub`
+    pub name: &'static str,
+    /// exact number of arguments including the name, or the negated minimum if variadic
+    pub arity: i64,
+    pub flags: &'static [&'static str],
+    /// position of the first key argument, 0 if the command takes no key
+    pub first_key: i64,
+    /// position of the last key argument, negative counts from the end
+    pub last_key: i64,
+    pub step: i64,
+    pub acl_categories: &'static [&'static str],
+    pub summary: &'static str,
+    pub since: &'static str,
+    pub group: &'static str,
+    pub complexity: &'static str,
+    pub subcommands: &'static [CommandSpec],
+    parse: ParseFn,
+}
+
+impl CommandSpec {
+    pub fn check_arity(&self, argc: usize) -> bool {
+        let argc = argc as i64;
+        if self.arity >= 0 {
+            argc == self.arity
+        } else {
+            argc >= -self.arity
+        }
+    }
+
+    /// the argument positions holding keys for a command line of `argc` arguments
+    pub fn key_positions(&self, argc: usize) -> Vec<usize> {
+        if self.first_key <= 0 {
+            return vec![];
+        }
+        let argc = argc as i64;
+        let last = if self.last_key < 0 {
+            argc + self.last_key
+        } else {
+            self.last_key.min(argc - 1)
+        };
+        (self.first_key..=last)
+            .step_by(self.step.max(1) as usize)
+            .map(|i| i as usize)
+            .collect()
+    }
+
+    pub fn subcommand(&self, name: &str) -> Option<&'static CommandSpec> {
+        let full = format!("{}|{}", self.name, name.to_ascii_lowercase());
+        SUBCOMMANDS.get(full.as_str()).copied()
+    }
+
+    pub(super) fn parse(&self, value: RespArray) -> Result<Command, CommandError> {
+        (self.parse)(value)
+    }
+}
+
+fn parse<T>(value: RespArray) -> Result<Command, CommandError>
+where
+    T: TryFrom<RespArray, Error = CommandError> + Into<Command>,
+{
+    Ok(T::try_from(value)?.into())
+}
+
+pub static COMMAND_TABLE: &[CommandSpec] = &[
+    CommandSpec {
+        name: "append",
+        arity: 3,
+        flags: &["write", "denyoom", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@write", "@string", "@fast"],
+        summary: "Appends a string to the value of a key. Creates the key if it doesn't exist.",
+        since: "2.0.0",
+        group: "string",
+        complexity: "O(1). The amortized time complexity is O(1) assuming the appended value is small and the already present value is of any size, since the dynamic string library used by Redis will double the free space available on every reallocation.",
+        subcommands: &[],
+        parse: parse::<Append>,
+    },
+    CommandSpec {
+        name: "client",
+        arity: -2,
+        flags: &[],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        acl_categories: &["@slow"],
+        summary: "A container for client connection commands.",
+        since: "2.4.0",
+        group: "connection",
+        complexity: "Depends on subcommand.",
+        subcommands: &[
+            CommandSpec {
+                name: "client|getname",
+                arity: 2,
+                flags: &["noscript", "loading", "stale"],
+                first_key: 0,
+                last_key: 0,
+                step: 0,
+                acl_categories: &["@slow", "@connection"],
+                summary: "Returns the name of the connection.",
+                since: "2.6.9",
+                group: "connection",
+                complexity: "O(1)",
+                subcommands: &[],
+                parse: parse::<ClientCommand>,
+            },
+            CommandSpec {
+                name: "client|id",
+                arity: 2,
+                flags: &["noscript", "loading", "stale"],
+                first_key: 0,
+                last_key: 0,
+                step: 0,
+                acl_categories: &["@slow", "@connection"],
+                summary: "Returns the unique client ID of the connection.",
+                since: "5.0.0",
+                group: "connection",
+                complexity: "O(1)",
+                subcommands: &[],
+                parse: parse::<ClientCommand>,
+            },
+            CommandSpec {
+                name: "client|info",
+                arity: 2,
+                flags: &["noscript", "loading", "stale"],
+                first_key: 0,
+                last_key: 0,
+                step: 0,
+                acl_categories: &["@slow", "@connection"],
+                summary: "Returns information about the connection.",
+                since: "6.2.0",
+                group: "connection",
+                complexity: "O(1)",
+                subcommands: &[],
+                parse: parse::<ClientCommand>,
+            },
+            CommandSpec {
+                name: "client|kill",
+                arity: -3,
+                flags: &["admin", "noscript", "loading", "stale"],
+                first_key: 0,
+                last_key: 0,
+                step: 0,
+                acl_categories: &["@admin", "@slow", "@dangerous", "@connection"],
+                summary: "Terminates open connections.",
+                since: "2.4.0",
+                group: "connection",
+                complexity: "O(N) where N is the number of client connections",
+                subcommands: &[],
+                parse: parse::<ClientCommand>,
+            },
+            CommandSpec {
+                name: "client|list",
+                arity: -2,
+                flags: &["admin", "noscript", "loading", "stale"],
+                first_key: 0,
+                last_key: 0,
+                step: 0,
+                acl_categories: &["@admin", "@slow", "@dangerous", "@connection"],
+                summary: "Lists open connections.",
+                since: "2.4.0",
+                group: "connection",
+                complexity: "O(N) where N is the number of client connections",
+                subcommands: &[],
+                parse: parse::<ClientCommand>,
+            },
+            CommandSpec {
+                name: "client|setinfo",
+                arity: 4,
+                flags: &["noscript", "loading", "stale"],
+                first_key: 0,
+                last_key: 0,
+                step: 0,
+                acl_categories: &["@slow", "@connection"],
+                summary: "Sets information specific to the client or connection.",
+                since: "7.2.0",
+                group: "connection",
+                complexity: "O(1)",
+                subcommands: &[],
+                parse: parse::<ClientCommand>,
+            },
+            CommandSpec {
+                name: "client|setname",
+                arity: 3,
+                flags: &["noscript", "loading", "stale"],
+                first_key: 0,
+                last_key: 0,
+                step: 0,
+                acl_categories: &["@slow", "@connection"],
+                summary: "Sets the connection name.",
+                since: "2.6.9",
+                group: "connection",
+                complexity: "O(1)",
+                subcommands: &[],
+                parse: parse::<ClientCommand>,
+            },
+        ],
+        parse: parse::<ClientCommand>,
+    },
+    CommandSpec {
+        name: "command",
+        arity: -1,
+        flags: &["loading", "stale"],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        acl_categories: &["@slow", "@connection"],
+        summary: "Returns detailed information about all commands.",
+        since: "2.8.13",
+        group: "server",
+        complexity: "O(N) where N is the total number of Redis commands",
+        subcommands: &[
+            CommandSpec {
+                name: "command|count",
+                arity: 2,
+                flags: &["loading", "stale"],
+                first_key: 0,
+                last_key: 0,
+                step: 0,
+                acl_categories: &["@slow", "@connection"],
+                summary: "Returns a count of commands.",
+                since: "2.8.13",
+                group: "server",
+                complexity: "O(1)",
+                subcommands: &[],
+                parse: parse::<CommandIntrospect>,
+            },
+            CommandSpec {
+                name: "command|docs",
+                arity: -2,
+                flags: &["loading", "stale"],
+                first_key: 0,
+                last_key: 0,
+                step: 0,
+                acl_categories: &["@slow", "@connection"],
+                summary: "Returns documentary information about one, multiple or all commands.",
+                since: "7.0.0",
+                group: "server",
+                complexity: "O(N) where N is the number of commands to look up",
+                subcommands: &[],
+                parse: parse::<CommandIntrospect>,
+            },
+            CommandSpec {
+                name: "command|getkeys",
+                arity: -3,
+                flags: &["loading", "stale"],
+                first_key: 0,
+                last_key: 0,
+                step: 0,
+                acl_categories: &["@slow", "@connection"],
+                summary: "Extracts the key names from an arbitrary command.",
+                since: "2.8.13",
+                group: "server",
+                complexity: "O(N) where N is the number of arguments to the command",
+                subcommands: &[],
+                parse: parse::<CommandIntrospect>,
+            },
+            CommandSpec {
+                name: "command|info",
+                arity: -2,
+                flags: &["loading", "stale"],
+                first_key: 0,
+                last_key: 0,
+                step: 0,
+                acl_categories: &["@slow", "@connection"],
+                summary: "Returns information about one, multiple or all commands.",
+                since: "2.8.13",
+                group: "server",
+                complexity: "O(N) where N is the number of commands to look up",
+                subcommands: &[],
+                parse: parse::<CommandIntrospect>,
+            },
+        ],
+        parse: parse::<CommandIntrospect>,
+    },
+    CommandSpec {
+        name: "decr",
+        arity: 2,
+        flags: &["write", "denyoom", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@write", "@string", "@fast"],
+        summary: "Decrements the integer value of a key by one. Uses 0 as initial value if the key doesn't exist.",
+        since: "1.0.0",
+        group: "string",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<IncrBy>,
+    },
+    CommandSpec {
+        name: "decrby",
+        arity: 3,
+        flags: &["write", "denyoom", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@write", "@string", "@fast"],
+        summary: "Decrements a number from the integer value of a key. Uses 0 as initial value if the key doesn't exist.",
+        since: "1.0.0",
+        group: "string",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<IncrBy>,
+    },
+    CommandSpec {
+        name: "echo",
+        arity: 2,
+        flags: &["loading", "stale", "fast"],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        acl_categories: &["@fast", "@connection"],
+        summary: "Returns the given string.",
+        since: "1.0.0",
+        group: "connection",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Echo>,
+    },
+    CommandSpec {
+        name: "expire",
+        arity: -3,
+        flags: &["write", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@keyspace", "@write", "@fast"],
+        summary: "Sets the expiration time of a key in seconds.",
+        since: "1.0.0",
+        group: "generic",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Expire>,
+    },
+    CommandSpec {
+        name: "expireat",
+        arity: -3,
+        flags: &["write", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@keyspace", "@write", "@fast"],
+        summary: "Sets the expiration time of a key to a Unix timestamp.",
+        since: "1.2.0",
+        group: "generic",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Expire>,
+    },
+    CommandSpec {
+        name: "expiretime",
+        arity: 2,
+        flags: &["readonly", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@keyspace", "@read", "@fast"],
+        summary: "Returns the expiration time of a key as a Unix timestamp.",
+        since: "7.0.0",
+        group: "generic",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Ttl>,
+    },
+    CommandSpec {
+        name: "get",
+        arity: 2,
+        flags: &["readonly", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@read", "@string", "@fast"],
+        summary: "Returns the string value of a key.",
+        since: "1.0.0",
+        group: "string",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Get>,
+    },
+    CommandSpec {
+        name: "getdel",
+        arity: 2,
+        flags: &["write", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@write", "@string", "@fast"],
+        summary: "Returns the string value of a key after deleting the key.",
+        since: "6.2.0",
+        group: "string",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<GetDel>,
+    },
+    CommandSpec {
+        name: "getex",
+        arity: -2,
+        flags: &["write", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@write", "@string", "@fast"],
+        summary: "Returns the string value of a key after setting its expiration time.",
+        since: "6.2.0",
+        group: "string",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<GetEx>,
+    },
+    CommandSpec {
+        name: "getrange",
+        arity: 4,
+        flags: &["readonly"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@read", "@string", "@slow"],
+        summary: "Returns a substring of the string stored at a key.",
+        since: "2.4.0",
+        group: "string",
+        complexity: "O(N) where N is the length of the returned string. The complexity is ultimately determined by the returned length, but because creating a substring from an existing string is very cheap, it can be considered O(1) for small strings.",
+        subcommands: &[],
+        parse: parse::<GetRange>,
+    },
+    CommandSpec {
+        name: "getset",
+        arity: 3,
+        flags: &["write", "denyoom", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@write", "@string", "@fast"],
+        summary: "Returns the previous string value of a key after setting it to a new value.",
+        since: "1.0.0",
+        group: "string",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Set>,
+    },
+    CommandSpec {
+        name: "hello",
+        arity: -1,
+        flags: &["noscript", "loading", "stale", "fast", "no_auth"],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        acl_categories: &["@fast", "@connection"],
+        summary: "Handshakes with the Redis server.",
+        since: "6.0.0",
+        group: "connection",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Hello>,
+    },
+    CommandSpec {
+        name: "hget",
+        arity: 3,
+        flags: &["readonly", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@read", "@hash", "@fast"],
+        summary: "Returns the value of a field in a hash.",
+        since: "2.0.0",
+        group: "hash",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<HGet>,
+    },
+    CommandSpec {
+        name: "hgetall",
+        arity: 2,
+        flags: &["readonly"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@read", "@hash", "@slow"],
+        summary: "Returns all fields and values in a hash.",
+        since: "2.0.0",
+        group: "hash",
+        complexity: "O(N) where N is the size of the hash.",
+        subcommands: &[],
+        parse: parse::<HGetAll>,
+    },
+    CommandSpec {
+        name: "hset",
+        arity: 4,
+        flags: &["write", "denyoom", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@write", "@hash", "@fast"],
+        summary: "Creates or modifies the value of a field in a hash.",
+        since: "2.0.0",
+        group: "hash",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<HSet>,
+    },
+    CommandSpec {
+        name: "incr",
+        arity: 2,
+        flags: &["write", "denyoom", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@write", "@string", "@fast"],
+        summary: "Increments the integer value of a key by one. Uses 0 as initial value if the key doesn't exist.",
+        since: "1.0.0",
+        group: "string",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<IncrBy>,
+    },
+    CommandSpec {
+        name: "incrby",
+        arity: 3,
+        flags: &["write", "denyoom", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@write", "@string", "@fast"],
+        summary: "Increments the integer value of a key by a number. Uses 0 as initial value if the key doesn't exist.",
+        since: "1.0.0",
+        group: "string",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<IncrBy>,
+    },
+    CommandSpec {
+        name: "incrbyfloat",
+        arity: 3,
+        flags: &["write", "denyoom", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@write", "@string", "@fast"],
+        summary: "Increment the floating point value of a key by a number. Uses 0 as initial value if the key doesn't exist.",
+        since: "2.6.0",
+        group: "string",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<IncrByFloat>,
+    },
+    CommandSpec {
+        name: "lcs",
+        arity: -3,
+        flags: &["readonly"],
+        first_key: 1,
+        last_key: 2,
+        step: 1,
+        acl_categories: &["@read", "@string", "@slow"],
+        summary: "Finds the longest common substring.",
+        since: "7.0.0",
+        group: "string",
+        complexity: "O(N*M) where N and M are the lengths of s1 and s2, respectively",
+        subcommands: &[],
+        parse: parse::<Lcs>,
+    },
+    CommandSpec {
+        name: "object",
+        arity: -2,
+        flags: &[],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        acl_categories: &["@slow"],
+        summary: "A container for object introspection commands.",
+        since: "2.2.3",
+        group: "generic",
+        complexity: "Depends on subcommand.",
+        subcommands: &[CommandSpec {
+            name: "object|encoding",
+            arity: 3,
+            flags: &["readonly"],
+            first_key: 2,
+            last_key: 2,
+            step: 1,
+            acl_categories: &["@keyspace", "@read", "@slow"],
+            summary: "Returns the internal encoding of a Redis object.",
+            since: "2.2.3",
+            group: "generic",
+            complexity: "O(1)",
+            subcommands: &[],
+            parse: parse::<Object>,
+        }],
+        parse: parse::<Object>,
+    },
+    CommandSpec {
+        name: "persist",
+        arity: 2,
+        flags: &["write", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@keyspace", "@write", "@fast"],
+        summary: "Removes the expiration time of a key.",
+        since: "2.2.0",
+        group: "generic",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Persist>,
+    },
+    CommandSpec {
+        name: "pexpire",
+        arity: -3,
+        flags: &["write", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@keyspace", "@write", "@fast"],
+        summary: "Sets the expiration time of a key in milliseconds.",
+        since: "2.6.0",
+        group: "generic",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Expire>,
+    },
+    CommandSpec {
+        name: "pexpireat",
+        arity: -3,
+        flags: &["write", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@keyspace", "@write", "@fast"],
+        summary: "Sets the expiration time of a key to a Unix milliseconds timestamp.",
+        since: "2.6.0",
+        group: "generic",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Expire>,
+    },
+    CommandSpec {
+        name: "pexpiretime",
+        arity: 2,
+        flags: &["readonly", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@keyspace", "@read", "@fast"],
+        summary: "Returns the expiration time of a key as a Unix milliseconds timestamp.",
+        since: "7.0.0",
+        group: "generic",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Ttl>,
+    },
+    CommandSpec {
+        name: "psetex",
+        arity: 4,
+        flags: &["write", "denyoom"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@write", "@string", "@slow"],
+        summary: "Sets both string value and expiration time in milliseconds of a key. The key is created if it doesn't exist.",
+        since: "2.6.0",
+        group: "string",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Set>,
+    },
+    CommandSpec {
+        name: "pttl",
+        arity: 2,
+        flags: &["readonly", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@keyspace", "@read", "@fast"],
+        summary: "Returns the expiration time in milliseconds of a key.",
+        since: "2.6.0",
+        group: "generic",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Ttl>,
+    },
+    CommandSpec {
+        name: "set",
+        arity: -3,
+        flags: &["write", "denyoom"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@write", "@string", "@slow"],
+        summary: "Sets the string value of a key, ignoring its type. The key is created if it doesn't exist.",
+        since: "1.0.0",
+        group: "string",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Set>,
+    },
+    CommandSpec {
+        name: "setex",
+        arity: 4,
+        flags: &["write", "denyoom"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@write", "@string", "@slow"],
+        summary: "Sets the string value and expiration time of a key. Creates the key if it doesn't exist.",
+        since: "2.0.0",
+        group: "string",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Set>,
+    },
+    CommandSpec {
+        name: "setnx",
+        arity: 3,
+        flags: &["write", "denyoom", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@write", "@string", "@fast"],
+        summary: "Set the string value of a key only when the key doesn't exist.",
+        since: "1.0.0",
+        group: "string",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<SetNx>,
+    },
+    CommandSpec {
+        name: "setrange",
+        arity: 4,
+        flags: &["write", "denyoom"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@write", "@string", "@slow"],
+        summary: "Overwrites a part of a string value with another by an offset. Creates the key if it doesn't exist.",
+        since: "2.2.0",
+        group: "string",
+        complexity: "O(1), not counting the time taken to copy the new string in place. Usually, this string is very small so the amortized complexity is O(1). Otherwise, complexity is O(M) with M being the length of the value argument.",
+        subcommands: &[],
+        parse: parse::<SetRange>,
+    },
+    CommandSpec {
+        name: "shutdown",
+        arity: -1,
+        flags: &["admin", "noscript", "loading", "stale", "no_multi", "allow_busy"],
+        first_key: 0,
+        last_key: 0,
+        step: 0,
+        acl_categories: &["@admin", "@slow", "@dangerous"],
+        summary: "Synchronously saves the database(s) to disk and shuts down the Redis server.",
+        since: "1.0.0",
+        group: "server",
+        complexity: "O(N) when saving, where N is the total number of keys in all databases when saving data, otherwise O(1)",
+        subcommands: &[],
+        parse: parse::<Shutdown>,
+    },
+    CommandSpec {
+        name: "strlen",
+        arity: 2,
+        flags: &["readonly", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@read", "@string", "@fast"],
+        summary: "Returns the length of a string value.",
+        since: "2.2.0",
+        group: "string",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<StrLen>,
+    },
+    CommandSpec {
+        name: "substr",
+        arity: 4,
+        flags: &["readonly"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@read", "@string", "@slow"],
+        summary: "Returns a substring from a string value.",
+        since: "1.0.0",
+        group: "string",
+        complexity: "O(N) where N is the length of the returned string. The complexity is ultimately determined by the returned length, but because creating a substring from an existing string is very cheap, it can be considered O(1) for small strings.",
+        subcommands: &[],
+        parse: parse::<GetRange>,
+    },
+    CommandSpec {
+        name: "ttl",
+        arity: 2,
+        flags: &["readonly", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@keyspace", "@read", "@fast"],
+        summary: "Returns the expiration time in seconds of a key.",
+        since: "1.0.0",
+        group: "generic",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Ttl>,
+    },
+    CommandSpec {
+        name: "type",
+        arity: 2,
+        flags: &["readonly", "fast"],
+        first_key: 1,
+        last_key: 1,
+        step: 1,
+        acl_categories: &["@keyspace", "@read", "@fast"],
+        summary: "Determines the type of value stored at a key.",
+        since: "1.0.0",
+        group: "generic",
+        complexity: "O(1)",
+        subcommands: &[],
+        parse: parse::<Type>,
+    },
+];
+
+lazy_static! {
+    static ref COMMANDS: HashMap<&'static str, &'static CommandSpec> =
+        COMMAND_TABLE.iter().map(|spec| (spec.name, spec)).collect();
+    static ref SUBCOMMANDS: HashMap<&'static str, &'static CommandSpec> = COMMAND_TABLE
+        .iter()
+        .flat_map(|spec| spec.subcommands.iter())
+        .map(|spec| (spec.name, spec))
+        .collect();
+}
+
+/// find a top level command by name, case insensitive
+pub fn lookup_command(name: &str) -> Option<&'static CommandSpec> {
+    COMMANDS.get(name.to_ascii_lowercase().as_str()).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_command() {
+        let spec = lookup_command("GET").unwrap();
+        assert_eq!(spec.name, "get");
+        assert!(spec.check_arity(2));
+        assert!(!spec.check_arity(3));
+
+        let spec = lookup_command("command").unwrap();
+        assert!(spec.check_arity(1));
+        assert!(spec.check_arity(5));
+        assert_eq!(spec.subcommand("COUNT").unwrap().name, "command|count");
+        assert!(spec.subcommand("nope").is_none());
+        assert!(lookup_command("command|info").is_none());
+        assert!(lookup_command("nope").is_none());
+    }
+
+    #[test]
+    fn test_key_positions() {
+        let spec = lookup_command("hset").unwrap();
+        assert_eq!(spec.key_positions(4), vec![1]);
+
+        let spec = lookup_command("echo").unwrap();
+        assert!(spec.key_positions(2).is_empty());
+    }
+}
@@ -0,0 +1,221 @@
+use crate::RespLimits;
+use anyhow::{anyhow, bail, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const DEFAULT_BIND: &str = "127.0.0.1";
+const DEFAULT_PORT: u16 = 6379;
+const DEFAULT_LOG_LEVEL: &str = "info";
+/// permissions of the unix socket when `unixsocketperm` is not set
+const DEFAULT_UNIX_SOCKET_PERM: u32 = 0o700;
+
+/// server configuration, loaded from a redis.conf style file and overridden by the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerConfig {
+    pub bind: String,
+    /// 0 disables the TCP listener
+    pub port: u16,
+    pub log_level: String,
+    /// path of the unix socket to listen on, next to or instead of TCP
+    pub unix_socket: Option<PathBuf>,
+    pub unix_socket_perm: u32,
+    /// bounds on the frames clients send
+    pub limits: RespLimits,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: DEFAULT_BIND.to_string(),
+            port: DEFAULT_PORT,
+            log_level: DEFAULT_LOG_LEVEL.to_string(),
+            unix_socket: None,
+            unix_socket_perm: DEFAULT_UNIX_SOCKET_PERM,
+            limits: RespLimits::default(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// load the config from a file, missing directives keep their default value
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow!("failed to read config file {}: {}", path.display(), e))?;
+        Self::parse(&content)
+    }
+
+    /// parse `directive value` lines, `#` starts a comment line
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut config = Self::default();
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let directive = parts.next().unwrap_or_default().to_ascii_lowercase();
+            let args = parts.collect::<Vec<_>>();
+            let Some(value) = args.first() else {
+                bail!("line {}: missing value for '{}'", i + 1, directive);
+            };
+
+            match directive.as_str() {
+                // only the first address is used for now
+                "bind" => config.bind = value.to_string(),
+                "port" => {
+                    config.port = value
+                        .parse()
+                        .map_err(|_| anyhow!("line {}: invalid port '{}'", i + 1, value))?
+                }
+                "loglevel" => config.log_level = parse_log_level(value)?.to_string(),
+                "unixsocket" => config.unix_socket = Some(PathBuf::from(value)),
+                // octal like chmod, e.g. 700
+                "unixsocketperm" => {
+                    config.unix_socket_perm = u32::from_str_radix(value, 8)
+                        .ok()
+                        .filter(|perm| *perm <= 0o777)
+                        .ok_or_else(|| {
+                            anyhow!("line {}: invalid unixsocketperm '{}'", i + 1, value)
+                        })?
+                }
+                "proto-max-bulk-len" => config.limits.max_bulk_len = parse_limit(i, value, true)?,
+                "proto-max-multibulk-len" => {
+                    config.limits.max_multibulk_len = parse_limit(i, value, false)?
+                }
+                "proto-max-nesting-depth" => {
+                    config.limits.max_nesting_depth = parse_limit(i, value, false)?
+                }
+                "proto-inline-max-size" => {
+                    config.limits.max_inline_len = parse_limit(i, value, true)?
+                }
+                _ => bail!("line {}: unknown directive '{}'", i + 1, directive),
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn addr(&self) -> String {
+        format!("{}:{}", self.bind, self.port)
+    }
+}
+
+/// a positive limit, sizes accept the redis memory units, e.g. `512mb`
+fn parse_limit(line: usize, value: &str, memory: bool) -> Result<usize> {
+    let n = if memory {
+        parse_memory(value)
+    } else {
+        value.parse().ok()
+    };
+    n.filter(|n| *n > 0)
+        .ok_or_else(|| anyhow!("line {}: invalid limit '{}'", line + 1, value))
+}
+
+/// `1k` is 1000 bytes and `1kb` is 1024 bytes, like in redis.conf
+fn parse_memory(value: &str) -> Option<usize> {
+    let value = value.to_ascii_lowercase();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (n, unit) = value.split_at(split);
+    let unit: usize = match unit {
+        "" | "b" => 1,
+        "k" => 1000,
+        "kb" => 1024,
+        "m" => 1000 * 1000,
+        "mb" => 1024 * 1024,
+        "g" => 1000 * 1000 * 1000,
+        "gb" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    n.parse::<usize>().ok()?.checked_mul(unit)
+}
+
+/// map redis log levels to tracing levels, tracing level names are accepted as is
+pub fn parse_log_level(level: &str) -> Result<&'static str> {
+    match level.to_ascii_lowercase().as_str() {
+        "trace" => Ok("trace"),
+        "debug" | "verbose" => Ok("debug"),
+        "info" | "notice" => Ok("info"),
+        "warn" | "warning" => Ok("warn"),
+        "error" | "nothing" => Ok("error"),
+        _ => bail!("invalid log level '{}'", level),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() -> Result<()> {
+        let content = "# comment\n\nbind 0.0.0.0\nport 6380\nloglevel warning\n";
+        let config = ServerConfig::parse(content)?;
+        assert_eq!(
+            config,
+            ServerConfig {
+                bind: "0.0.0.0".to_string(),
+                port: 6380,
+                log_level: "warn".to_string(),
+                ..Default::default()
+            }
+        );
+        assert_eq!(config.addr(), "0.0.0.0:6380");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_config_unix_socket() -> Result<()> {
+        let config = ServerConfig::parse(
+            "port 0
+unixsocket /tmp/redis.sock
+unixsocketperm 770",
+        )?;
+        assert_eq!(config.port, 0);
+        assert_eq!(config.unix_socket, Some(PathBuf::from("/tmp/redis.sock")));
+        assert_eq!(config.unix_socket_perm, 0o770);
+        assert!(ServerConfig::parse("unixsocketperm 800").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_config_limits() -> Result<()> {
+        let config = ServerConfig::parse(
+            "proto-max-bulk-len 1mb
+proto-max-multibulk-len 1000
+proto-max-nesting-depth 8
+proto-inline-max-size 4k",
+        )?;
+        assert_eq!(
+            config.limits,
+            RespLimits {
+                max_bulk_len: 1024 * 1024,
+                max_multibulk_len: 1000,
+                max_nesting_depth: 8,
+                max_inline_len: 4000,
+            }
+        );
+        assert!(ServerConfig::parse("proto-max-bulk-len 0").is_err());
+        assert!(ServerConfig::parse("proto-max-bulk-len 10xb").is_err());
+        assert!(ServerConfig::parse("proto-max-nesting-depth 1kb").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_config_default() -> Result<()> {
+        let config = ServerConfig::parse("")?;
+        assert_eq!(config, ServerConfig::default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_config_error() {
+        assert!(ServerConfig::parse("port abc").is_err());
+        assert!(ServerConfig::parse("port").is_err());
+        assert!(ServerConfig::parse("unknown 1").is_err());
+        assert!(ServerConfig::parse("loglevel loud").is_err());
+    }
+}
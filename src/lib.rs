@@ -1,8 +1,12 @@
 mod backend;
+mod config;
 mod protocol;
+mod session;
 
 pub mod cmd;
 pub mod network;
 
 pub use backend::*;
+pub use config::*;
 pub use protocol::*;
+pub use session::*;
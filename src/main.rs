@@ -1,5 +1,220 @@
-mod protocol;
+use anyhow::{bail, Result};
+use clap::Parser;
+use imitate_redis::{network, parse_log_level, Backend, ServerConfig, ShutdownMode};
+#[cfg(unix)]
+use std::path::Path;
+use std::{path::PathBuf, time::Duration};
+#[cfg(unix)]
+use tokio::net::UnixListener;
+use tokio::{net::TcpListener, time::timeout};
+use tokio_util::task::TaskTracker;
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
 
-fn main() {
-    println!("Hello, world!");
+/// a redis compatible server
+#[derive(Debug, Parser)]
+#[command(name = "imitate-redis", version, about)]
+struct Args {
+    /// path to a redis.conf style config file
+    config: Option<PathBuf>,
+    /// address to bind, overrides `bind` in the config file
+    #[arg(long)]
+    bind: Option<String>,
+    /// port to listen on, overrides `port` in the config file
+    #[arg(short, long)]
+    port: Option<u16>,
+    /// unix socket path to listen on, overrides `unixsocket` in the config file
+    #[arg(long)]
+    unixsocket: Option<PathBuf>,
+    /// log level (trace, debug, info, warn, error or the redis names), overrides `loglevel`
+    #[arg(long)]
+    log_level: Option<String>,
+}
+
+impl Args {
+    fn into_config(self) -> Result<ServerConfig> {
+        let mut config = match self.config {
+            Some(path) => ServerConfig::load(path)?,
+            None => ServerConfig::default(),
+        };
+        if let Some(bind) = self.bind {
+            config.bind = bind;
+        }
+        if let Some(port) = self.port {
+            config.port = port;
+        }
+        if let Some(path) = self.unixsocket {
+            config.unix_socket = Some(path);
+        }
+        if let Some(level) = self.log_level {
+            config.log_level = parse_log_level(&level)?.to_string();
+        }
+        Ok(config)
+    }
+}
+
+/// how long connections get to finish their in-flight commands on shutdown
+const SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+/// pause after a failed accept, errors like EMFILE persist and would spin the loop
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let config = Args::parse().into_config()?;
+
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&config.log_level));
+    tracing_subscriber::fmt().with_env_filter(filter).init();
+
+    let backend = Backend::with_limits(config.limits);
+    let expire = tokio::spawn({
+        let backend = backend.clone();
+        async move { backend.run_active_expire().await }
+    });
+    let connections = TaskTracker::new();
+    let listeners = TaskTracker::new();
+    if config.port != 0 {
+        let addr = config.addr();
+        let listener = TcpListener::bind(&addr).await?;
+        info!("imitate-redis listening on {}", addr);
+        listeners.spawn(accept_tcp(listener, backend.clone(), connections.clone()));
+    }
+    #[cfg(unix)]
+    if let Some(path) = config.unix_socket.clone() {
+        let listener = bind_unix(&path, config.unix_socket_perm)?;
+        info!("imitate-redis listening on unix socket {}", path.display());
+        listeners.spawn(accept_unix(
+            listener,
+            path,
+            backend.clone(),
+            connections.clone(),
+        ));
+    }
+    if listeners.is_empty() {
+        bail!("nothing to listen on, set a port or a unixsocket");
+    }
+
+    tokio::select! {
+        _ = shutdown_signal() => backend.shutdown(ShutdownMode::default()),
+        _ = backend.shutting_down() => {}
+    }
+    // the accept loops return once the shutdown starts, dropping their listener
+    listeners.close();
+    listeners.wait().await;
+    #[cfg(unix)]
+    if let Some(path) = &config.unix_socket {
+        let _ = std::fs::remove_file(path);
+    }
+
+    info!("Shutting down, closing {} connections", connections.len());
+    connections.close();
+    // connections finish their current batch, one stuck on a client that doesn't read is dropped
+    if timeout(SHUTDOWN_DRAIN_TIMEOUT, connections.wait())
+        .await
+        .is_err()
+    {
+        warn!("Connections still open after {:?}", SHUTDOWN_DRAIN_TIMEOUT);
+    }
+    let _ = expire.await;
+    backend.finish_shutdown()?;
+    info!("imitate-redis is now ready to exit, bye bye...");
+    Ok(())
+}
+
+async fn accept_tcp(listener: TcpListener, backend: Backend, connections: TaskTracker) {
+    loop {
+        let (stream, raddr) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("Accepting client connection: {:?}", e);
+                    tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                    continue;
+                }
+            },
+            _ = backend.shutting_down() => return,
+        };
+        info!("Accepted connection from: {}", raddr);
+        let backend = backend.clone();
+        connections.spawn(async move {
+            match network::stream_handler(stream, backend).await {
+                Ok(_) => info!("Connection from {} exited", raddr),
+                Err(e) => warn!("handle error for {}: {:?}", raddr, e),
+            }
+        });
+    }
+}
+
+/// bind the unix socket, replacing a stale socket file left by a previous run
+#[cfg(unix)]
+fn bind_unix(path: &Path, perm: u32) -> Result<UnixListener> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(perm))?;
+    Ok(listener)
+}
+
+#[cfg(unix)]
+async fn accept_unix(
+    listener: UnixListener,
+    path: PathBuf,
+    backend: Backend,
+    connections: TaskTracker,
+) {
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    warn!("Accepting client connection: {:?}", e);
+                    tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                    continue;
+                }
+            },
+            _ = backend.shutting_down() => return,
+        };
+        info!("Accepted connection to {}", path.display());
+        let (path, backend) = (path.clone(), backend.clone());
+        connections.spawn(async move {
+            match network::unix_stream_handler(stream, &path, backend).await {
+                Ok(_) => info!("Connection to {} exited", path.display()),
+                Err(e) => warn!("handle error for {}: {:?}", path.display(), e),
+            }
+        });
+    }
+}
+
+/// resolves on SIGINT, or SIGTERM on unix
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for SIGINT: {:?}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {:?}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received SIGINT, scheduling shutdown..."),
+        _ = terminate => info!("Received SIGTERM, scheduling shutdown..."),
+    }
 }
@@ -1,51 +1,227 @@
 use crate::{
-    cmd::{Command, CommandExecutor},
-    Backend, RespDecode, RespEncode, RespError, RespFrame,
+    cmd::{lookup_command, Command, CommandExecutor},
+    decode_inline, Backend, Client, RespArray, RespDecoder, RespEncode, RespError, RespFrame,
+    RespLimits, RespVersion, Session, SimpleError,
 };
 use anyhow::Result;
-
-use tokio::net::TcpStream;
+use futures::{FutureExt, SinkExt};
+use std::{io::ErrorKind, time::Instant};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+};
 use tokio_stream::StreamExt;
 use tokio_util::codec::{Decoder, Encoder, Framed};
-use tracing::info;
+use tracing::{debug, warn};
 
-#[derive(Debug)]
-struct RespFrameCodec;
+/// keeps the decoder state between reads, so a frame split over many reads is parsed once
+#[derive(Debug, Default)]
+struct RespFrameCodec {
+    decoder: RespDecoder,
+}
+
+impl RespFrameCodec {
+    fn new(limits: RespLimits) -> Self {
+        Self {
+            decoder: RespDecoder::with_limits(limits),
+        }
+    }
+}
 
 #[derive(Debug)]
-#[allow(unused)]
 struct RedisRequest {
     frame: RespFrame,
     backend: Backend,
 }
 
 #[derive(Debug)]
-#[allow(unused)]
 struct RedisResponse {
-    frame: RespFrame,
+    /// none when the command sends no reply
+    frame: Option<RespFrame>,
+}
+
+/// max number of pipelined requests executed before their replies are flushed
+const MAX_PIPELINE_BATCH: usize = 1024;
+/// buffered reply bytes that make `feed` write to the socket before accepting more replies
+const WRITE_BACKPRESSURE_BOUNDARY: usize = 64 * 1024;
+/// sent to every client before its connection is closed by a shutdown
+const SHUTDOWN_MESSAGE: &str = "ERR Server is shutting down";
+
+/// serve a TCP client until it disconnects. Every complete frame already read is executed in
+/// order and their replies are written with a single flush.
+pub async fn stream_handler(stream: TcpStream, backend: Backend) -> Result<()> {
+    let client = Client::new(
+        stream.peer_addr()?.to_string(),
+        stream.local_addr()?.to_string(),
+    );
+    connection_handler(stream, client, backend).await
 }
 
-pub async fn stream_handler(stream: TcpStream, _backend: Backend) -> Result<()> {
-    let mut framed = Framed::new(stream, RespFrameCodec);
+/// serve a client of the unix socket at `path`, the same way as `stream_handler`
+#[cfg(unix)]
+pub async fn unix_stream_handler(
+    stream: tokio::net::UnixStream,
+    path: impl AsRef<std::path::Path>,
+    backend: Backend,
+) -> Result<()> {
+    connection_handler(stream, Client::new_unix(path), backend).await
+}
+
+async fn connection_handler<S>(stream: S, client: Client, backend: Backend) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let session = Session::with_client(client);
+    let id = session.id();
+    backend.register_client(session.client().clone());
+    let ret = serve(stream, &backend, session).await;
+    backend.unregister_client(id);
+    ret
+}
+
+async fn serve<S>(stream: S, backend: &Backend, mut session: Session) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let client = session.client().clone();
+    let mut framed = Framed::new(stream, RespFrameCodec::new(*backend.limits()));
+    // a client that never reads its replies stalls `feed` here, so we stop reading its requests
+    framed.set_backpressure_boundary(WRITE_BACKPRESSURE_BOUNDARY);
     loop {
-        let _result = framed.next().await.unwrap();
+        let mut next = tokio::select! {
+            next = framed.next() => next,
+            _ = client.killed() => return Ok(()),
+            _ = backend.shutting_down() => return close_for_shutdown(&mut framed).await,
+        };
+        let mut batch = 0;
+        let result = loop {
+            let frame = match next {
+                Some(Ok(frame)) => frame,
+                Some(Err(e)) => break Err(e),
+                None => break Ok(false),
+            };
+
+            debug!("Received frame: {:?}", frame);
+            let request = RedisRequest {
+                frame,
+                backend: backend.clone(),
+            };
+            let response = request_handler(request, &mut session).await?;
+            if let Some(frame) = response.frame {
+                debug!("Sending response: {:?}", frame);
+                framed.feed(frame).await?;
+            }
+
+            batch += 1;
+            // a killed client gets the replies of the commands it already ran, then is closed
+            if batch >= MAX_PIPELINE_BATCH || client.is_killed() || backend.is_shutting_down() {
+                break Ok(true);
+            }
+            // only take what is ready, waiting for more input would hold back the replies
+            next = match framed.next().now_or_never() {
+                Some(next) => next,
+                None => break Ok(true),
+            };
+        };
+        {
+            let mut state = client.state();
+            state.qbuf = framed.read_buffer().len();
+            state.qbuf_free = framed.read_buffer().capacity() - state.qbuf;
+            state.omem = framed.write_buffer().len();
+        }
+        framed.flush().await?;
+
+        match result {
+            Ok(true) if client.is_killed() => return Ok(()),
+            Ok(true) if backend.is_shutting_down() => return close_for_shutdown(&mut framed).await,
+            Ok(true) => {}
+            Ok(false) => return Ok(()),
+            Err(e) => {
+                if is_disconnect(&e) {
+                    return Ok(());
+                }
+                // the stream can't be resynchronized after a bad frame, reply and close it
+                if let Some(e) = e.downcast_ref::<RespError>() {
+                    warn!("Protocol error: {}", e);
+                    framed.send(SimpleError::from(e.clone()).into()).await?;
+                }
+                return Err(e);
+            }
+        }
+    }
+}
+
+async fn close_for_shutdown<S>(framed: &mut Framed<S, RespFrameCodec>) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    match framed.send(SimpleError::new(SHUTDOWN_MESSAGE).into()).await {
+        Err(e) if !is_disconnect(&e) => Err(e),
+        _ => Ok(()),
     }
 }
-#[allow(unused)]
-async fn request_handler(request: RedisRequest) -> Result<RedisResponse> {
+
+async fn request_handler(request: RedisRequest, session: &mut Session) -> Result<RedisResponse> {
     let (frame, backend) = (request.frame, request.backend);
-    let cmd = Command::try_from(frame)?;
-    info!("Executing command: {:?}", cmd);
-    let frame = cmd.execute(&backend);
+    {
+        let mut state = session.client().state();
+        state.last_cmd = command_name(&frame);
+        state.last_interaction = Instant::now();
+    }
+    let frame = match Command::try_from(frame) {
+        Ok(cmd) => {
+            debug!("Executing command: {:?}", cmd);
+            cmd.execute(&backend, session)
+        }
+        Err(e) => e.into(),
+    };
+    // the reply is encoded with the protocol in effect after the command, as HELLO expects
+    let frame = match session.protocol() {
+        RespVersion::Resp2 => frame.into_resp2(),
+        RespVersion::Resp3 => frame,
+    };
+    let frame = (!session.take_skip_reply()).then_some(frame);
     Ok(RedisResponse { frame })
 }
 
+/// the command name as CLIENT LIST reports it, `container|sub` for subcommands
+fn command_name(frame: &RespFrame) -> Option<String> {
+    let RespFrame::Array(RespArray(Some(args))) = frame else {
+        return None;
+    };
+    let name = |arg: Option<&RespFrame>| match arg {
+        Some(RespFrame::BulkString(name)) => {
+            Some(String::from_utf8_lossy(name.as_ref()).to_lowercase())
+        }
+        _ => None,
+    };
+    let spec = lookup_command(&name(args.first())?)?;
+    match name(args.get(1)) {
+        Some(sub) if !spec.subcommands.is_empty() => {
+            spec.subcommand(&sub).map(|spec| spec.name.to_string())
+        }
+        _ => Some(spec.name.to_string()),
+    }
+}
+
+/// the peer went away, which ends the connection without being an error
+fn is_disconnect(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<std::io::Error>().is_some_and(|e| {
+        matches!(
+            e.kind(),
+            ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::BrokenPipe
+                | ErrorKind::UnexpectedEof
+        )
+    })
+}
+
 impl Encoder<RespFrame> for RespFrameCodec {
     type Error = anyhow::Error;
 
     fn encode(&mut self, item: RespFrame, dst: &mut bytes::BytesMut) -> Result<()> {
-        let encoded = item.encode();
-        dst.extend_from_slice(&encoded);
+        item.encode_to(dst);
         Ok(())
     }
 }
@@ -55,10 +231,310 @@ impl Decoder for RespFrameCodec {
     type Error = anyhow::Error;
 
     fn decode(&mut self, src: &mut bytes::BytesMut) -> Result<Option<RespFrame>> {
-        match RespFrame::decode(src) {
-            Ok(frame) => Ok(Some(frame)),
-            Err(RespError::NotComplete) => Ok(None),
-            Err(e) => Err(e.into()),
+        loop {
+            let result = match src.first() {
+                None => return Ok(None),
+                // a frame in progress stays at the start of the buffer until it is complete
+                Some(b'*') => return Ok(self.decoder.decode(src)?),
+                // telnet style commands, e.g. `SET a b\r\n`. Like redis, only `*` starts a frame,
+                // a line starting with another type byte is an inline command too.
+                Some(_) => match decode_inline(src, self.decoder.limits().max_inline_len) {
+                    // empty lines are skipped like redis does
+                    Ok(RespArray(Some(args))) if args.is_empty() => continue,
+                    result => result.map(RespFrame::from),
+                },
+            };
+            return match result {
+                Ok(frame) => Ok(Some(frame)),
+                Err(RespError::NotComplete) => Ok(None),
+                Err(e) => Err(e.into()),
+            };
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    #[tokio::test]
+    async fn test_stream_handler() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await?;
+            stream_handler(stream, Backend::new()).await
+        });
+
+        let mut client = TcpStream::connect(addr).await?;
+        client
+            .write_all(b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
+            .await?;
+        let mut buf = [0u8; 64];
+        let n = client.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"+OK\r\n");
+
+        client
+            .write_all(b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n")
+            .await?;
+        let n = client.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"$5\r\nworld\r\n");
+
+        // closing the client ends the handler cleanly
+        drop(client);
+        server.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_handler_protocol_error() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await?;
+            stream_handler(stream, Backend::new()).await
+        });
+
+        let mut client = TcpStream::connect(addr).await?;
+        client.write_all(b"*1\r\n$x\r\nping\r\n").await?;
+        let mut buf = Vec::new();
+        client.read_to_end(&mut buf).await?;
+        assert!(buf.starts_with(b"-ERR Protocol error"));
+
+        assert!(server.await?.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_handler_limits() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let backend = Backend::with_limits(RespLimits {
+            max_bulk_len: 8,
+            ..Default::default()
+        });
+        let server = tokio::spawn(async move {
+            let mut handlers = Vec::new();
+            for _ in 0..2 {
+                let (stream, _) = listener.accept().await?;
+                handlers.push(tokio::spawn(stream_handler(stream, backend.clone())));
+            }
+            let mut results = Vec::new();
+            for handler in handlers {
+                results.push(handler.await?.is_ok());
+            }
+            anyhow::Ok(results)
+        });
+
+        let mut bad = TcpStream::connect(addr).await?;
+        let mut good = TcpStream::connect(addr).await?;
+        bad.write_all(b"*2\r\n$4\r\necho\r\n$1000000000\r\n")
+            .await?;
+        let mut buf = Vec::new();
+        bad.read_to_end(&mut buf).await?;
+        assert_eq!(buf, b"-ERR Protocol error: invalid bulk length\r\n");
+
+        // the other connection is not affected
+        good.write_all(b"*2\r\n$4\r\necho\r\n$1\r\na\r\n").await?;
+        let mut buf = [0u8; 16];
+        let n = good.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"$1\r\na\r\n");
+        drop(good);
+
+        assert_eq!(server.await??, vec![false, true]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_inline_command() -> Result<()> {
+        let mut buf = bytes::BytesMut::from("\r\nPING\r\n*1\r\n$4\r\nping\r\n");
+        let mut codec = RespFrameCodec::default();
+
+        let frame = codec.decode(&mut buf)?;
+        assert_eq!(frame, Some(RespArray::new([b"PING".into()]).into()));
+        let frame = codec.decode(&mut buf)?;
+        assert_eq!(frame, Some(RespArray::new([b"ping".into()]).into()));
+        assert_eq!(codec.decode(&mut buf)?, None);
+
+        let mut buf = bytes::BytesMut::from("-foo\r\n:1 $2\r\n");
+        let frame = codec.decode(&mut buf)?;
+        assert_eq!(frame, Some(RespArray::new([b"-foo".into()]).into()));
+        let frame = codec.decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            Some(RespArray::new([b":1".into(), b"$2".into()]).into())
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_handler_pipeline() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await?;
+            stream_handler(stream, Backend::new()).await
+        });
+
+        let mut client = TcpStream::connect(addr).await?;
+        let mut request = Vec::new();
+        let mut expected = Vec::new();
+        for i in 0..2000 {
+            let value = i.to_string();
+            request.extend_from_slice(
+                format!("*2\r\n$4\r\necho\r\n${}\r\n{}\r\n", value.len(), value).as_bytes(),
+            );
+            expected.extend_from_slice(format!("${}\r\n{}\r\n", value.len(), value).as_bytes());
+        }
+        client.write_all(&request).await?;
+        client.shutdown().await?;
+
+        let mut buf = Vec::new();
+        client.read_to_end(&mut buf).await?;
+        assert_eq!(buf, expected);
+
+        server.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_handler_client_kill() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let backend = Backend::new();
+        let server_backend = backend.clone();
+        let server = tokio::spawn(async move {
+            let mut handlers = Vec::new();
+            for _ in 0..2 {
+                let (stream, _) = listener.accept().await?;
+                handlers.push(tokio::spawn(stream_handler(stream, server_backend.clone())));
+            }
+            for handler in handlers {
+                handler.await??;
+            }
+            anyhow::Ok(())
+        });
+
+        let mut victim = TcpStream::connect(addr).await?;
+        let victim_addr = victim.local_addr()?.to_string();
+        let mut killer = TcpStream::connect(addr).await?;
+        let mut buf = [0u8; 64];
+
+        // wait until both connections are registered
+        killer.write_all(b"ECHO a\r\n").await?;
+        let n = killer.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"$1\r\na\r\n");
+        while backend.clients().len() < 2 {
+            tokio::task::yield_now().await;
+        }
+
+        let request = format!(
+            "*3\r\n$6\r\nclient\r\n$4\r\nkill\r\n${}\r\n{}\r\n",
+            victim_addr.len(),
+            victim_addr
+        );
+        killer.write_all(request.as_bytes()).await?;
+        let n = killer.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"+OK\r\n");
+
+        let n = victim.read(&mut buf).await?;
+        assert_eq!(n, 0);
+
+        drop(killer);
+        server.await??;
+        assert!(backend.clients().is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_handler_shutdown() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let backend = Backend::new();
+        let server_backend = backend.clone();
+        let server = tokio::spawn(async move {
+            let mut handlers = Vec::new();
+            for _ in 0..2 {
+                let (stream, _) = listener.accept().await?;
+                handlers.push(tokio::spawn(stream_handler(stream, server_backend.clone())));
+            }
+            for handler in handlers {
+                handler.await??;
+            }
+            anyhow::Ok(())
+        });
+
+        let mut other = TcpStream::connect(addr).await?;
+        let mut client = TcpStream::connect(addr).await?;
+        while backend.clients().len() < 2 {
+            tokio::task::yield_now().await;
+        }
+        client
+            .write_all(b"SET a 1\r\nSHUTDOWN NOSAVE\r\nGET a\r\n")
+            .await?;
+        let mut buf = Vec::new();
+        client.read_to_end(&mut buf).await?;
+        // the pipeline stops at SHUTDOWN, which isn't replied to, then the connection is closed
+        assert_eq!(buf, b"+OK\r\n".to_vec());
+
+        // the other clients are told before their connection is closed
+        let mut buf = Vec::new();
+        other.read_to_end(&mut buf).await?;
+        assert_eq!(buf, b"-ERR Server is shutting down\r\n".to_vec());
+        server.await??;
+        assert!(backend.is_shutting_down());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_stream_handler() -> Result<()> {
+        use tokio::net::{UnixListener, UnixStream};
+
+        let path = std::env::temp_dir().join(format!("imitate-redis-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path)?;
+        let backend = Backend::new();
+        let server_path = path.clone();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await?;
+            unix_stream_handler(stream, server_path, backend).await
+        });
+
+        let mut client = UnixStream::connect(&path).await?;
+        client.write_all(b"CLIENT INFO\r\n").await?;
+        let mut buf = [0u8; 512];
+        let n = client.read(&mut buf).await?;
+        let info = String::from_utf8_lossy(&buf[..n]).to_string();
+        let addr = format!("addr={}:0 ", path.display());
+        assert!(info.contains(&addr), "{}", info);
+        assert!(info.contains(" flags=U "), "{}", info);
+
+        drop(client);
+        server.await??;
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_split_frame() -> Result<()> {
+        let mut codec = RespFrameCodec::default();
+        let mut buf = bytes::BytesMut::from(&b"*3\r\n$3\r\nset\r\n:1\r\n$5\r\nhel"[..]);
+        assert_eq!(codec.decode(&mut buf)?, None);
+
+        buf.extend_from_slice(b"lo\r\nPING\r\n");
+        assert_eq!(
+            codec.decode(&mut buf)?,
+            Some(RespArray::new([b"set".into(), 1.into(), b"hello".into()]).into())
+        );
+        assert_eq!(
+            codec.decode(&mut buf)?,
+            Some(RespArray::new([b"PING".into()]).into())
+        );
+        Ok(())
+    }
+}
@@ -1,9 +1,8 @@
-use bytes::{Buf, BytesMut};
+use bytes::{BufMut, BytesMut};
 use std::ops::Deref;
 
 use super::{RespDecode, RespEncode, RespError, RespFrame};
 
-const NULL: &str = "*-1\r\n";
 /// *<number-of-elements>\r\n<element-1>...<element-n>
 ///
 
@@ -11,19 +10,17 @@ const NULL: &str = "*-1\r\n";
 pub struct RespArray(pub(crate) Option<Vec<RespFrame>>);
 
 impl RespEncode for RespArray {
-    fn encode(self) -> Vec<u8> {
-        match self.0 {
-            Some(frame) => {
-                let mut bytes = Vec::with_capacity(1024);
+    fn encode_to<B: BufMut>(&self, buf: &mut B) {
+        match &self.0 {
+            Some(frames) => {
                 // * number-of-elements \r\n
-                bytes.extend_from_slice(&format!("*{}\r\n", frame.len()).into_bytes());
+                super::put_prefixed_int(buf, b'*', frames.len());
                 // <element-1>...<element-n>
-                frame.into_iter().for_each(|f| {
-                    bytes.extend_from_slice(&f.encode());
-                });
-                bytes
+                for frame in frames {
+                    frame.encode_to(buf);
+                }
             }
-            None => b"*-1\r\n".to_vec(),
+            None => buf.put_slice(b"*-1\r\n"),
         }
     }
 }
@@ -33,35 +30,10 @@ impl RespDecode for RespArray {
     const TYPE: &'static str = "array";
 
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let is_null = super::extract_fixed_data_noadv(buf, NULL, "NullArray");
-        match is_null {
-            Ok(_) => {
-                buf.advance(NULL.len());
-                return Ok(RespArray::new_null());
-            }
-            Err(RespError::NotComplete) => return Err(RespError::NotComplete),
-            _ => {}
-        }
-        let (end, len) = super::parse_length(buf, Self::PREFIX)?;
-        let total_len = super::calc_total_length(buf, end, len, Self::PREFIX)?;
-
-        if buf.len() < total_len {
-            return Err(RespError::NotComplete);
-        }
-
-        buf.advance(end + super::CRLF_LEN);
-
-        let mut frames = Vec::with_capacity(len);
-        for _ in 0..len {
-            frames.push(RespFrame::decode(buf)?);
-        }
-
-        Ok(RespArray::new(frames))
-    }
-
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
-        let (end, len) = super::parse_length(buf, Self::PREFIX)?;
-        super::calc_total_length(buf, end, len, Self::PREFIX)
+        super::decode_frame_as(buf, Self::PREFIX, |frame| match frame {
+            RespFrame::Array(v) => Some(v),
+            _ => None,
+        })
     }
 }
 
@@ -143,4 +115,11 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_array_decode_partial_element() {
+        let mut buf = BytesMut::from(&b"*2\r\n$3\r\nset\r\n$5\r\nhel"[..]);
+        let ret = RespArray::decode(&mut buf);
+        assert_eq!(ret.unwrap_err(), RespError::NotComplete);
+    }
 }
@@ -0,0 +1,84 @@
+use bytes::{BufMut, BytesMut};
+
+use super::{RespDecode, RespEncode, RespError, RespFrame, RespMap};
+
+/// |<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n><frame>
+/// auxiliary data, like key popularity, sent ahead of the reply it describes
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct RespAttribute {
+    pub(crate) attrs: RespMap,
+    pub(crate) frame: Box<RespFrame>,
+}
+
+impl RespEncode for RespAttribute {
+    fn encode_to<B: BufMut>(&self, buf: &mut B) {
+        super::put_prefixed_int(buf, b'|', self.attrs.len());
+        self.attrs.encode_entries_to(buf);
+        self.frame.encode_to(buf);
+    }
+}
+
+impl RespDecode for RespAttribute {
+    const PREFIX: &'static str = "|";
+    const TYPE: &'static str = "attribute";
+
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        super::decode_frame_as(buf, Self::PREFIX, |frame| match frame {
+            RespFrame::Attribute(v) => Some(v),
+            _ => None,
+        })
+    }
+}
+
+impl RespAttribute {
+    pub fn new(attrs: RespMap, frame: impl Into<RespFrame>) -> Self {
+        RespAttribute {
+            attrs,
+            frame: Box::new(frame.into()),
+        }
+    }
+
+    pub fn attrs(&self) -> &RespMap {
+        &self.attrs
+    }
+
+    pub fn frame(&self) -> &RespFrame {
+        &self.frame
+    }
+
+    /// the reply without its attributes
+    pub fn into_frame(self) -> RespFrame {
+        *self.frame
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespArray;
+    use anyhow::Result;
+
+    #[test]
+    fn test_attribute() -> Result<()> {
+        let mut attrs = RespMap::new();
+        attrs.insert("ttl", 3600);
+        let attr = RespAttribute::new(attrs, RespArray::new([2.into()]));
+        let encoded = RespFrame::from(attr.clone()).encode();
+        assert_eq!(encoded, b"|1\r\n+ttl\r\n:3600\r\n*1\r\n:2\r\n");
+
+        let mut buf = BytesMut::from(&encoded[..]);
+        assert_eq!(RespAttribute::decode(&mut buf)?, attr);
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_attribute_not_complete() {
+        // the attributes alone are not a frame, the reply must follow
+        let mut buf = BytesMut::from("|1\r\n+ttl\r\n:3600\r\n");
+        assert_eq!(
+            RespAttribute::decode(&mut buf).unwrap_err(),
+            RespError::NotComplete
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use bytes::{BufMut, BytesMut};
+
+use super::{RespDecode, RespEncode, RespError, RespFrame, CRLF};
+
+/// ([+|-]<number>\r\n
+/// an integer out of the i64 range, kept as its decimal digits
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+pub struct RespBigNumber(pub(crate) String);
+
+impl RespEncode for RespBigNumber {
+    fn encode_to<B: BufMut>(&self, buf: &mut B) {
+        buf.put_u8(b'(');
+        buf.put_slice(self.0.as_bytes());
+        buf.put_slice(CRLF);
+    }
+}
+
+impl RespDecode for RespBigNumber {
+    const PREFIX: &'static str = "(";
+    const TYPE: &'static str = "big_number";
+
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        super::decode_frame_as(buf, Self::PREFIX, |frame| match frame {
+            RespFrame::BigNumber(v) => Some(v),
+            _ => None,
+        })
+    }
+}
+
+impl RespBigNumber {
+    /// `s` must be an optional sign followed by decimal digits
+    pub fn new(s: impl Into<String>) -> Result<Self, RespError> {
+        let s = s.into();
+        let digits = s.strip_prefix(['+', '-']).unwrap_or(&s);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(RespError::InvalidFrame(format!(
+                "Invalid big number: {:?}",
+                s
+            )));
+        }
+        Ok(RespBigNumber(s))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_big_number() -> Result<()> {
+        let n = RespBigNumber::new("-3492890328409238509324850943850943825024385")?;
+        let encoded = RespFrame::from(n.clone()).encode();
+        assert_eq!(
+            encoded,
+            b"(-3492890328409238509324850943850943825024385\r\n"
+        );
+
+        let mut buf = BytesMut::from(&encoded[..]);
+        assert_eq!(RespBigNumber::decode(&mut buf)?, n);
+        Ok(())
+    }
+
+    #[test]
+    fn test_big_number_invalid() {
+        assert!(RespBigNumber::new("12a").is_err());
+        assert!(RespBigNumber::new("-").is_err());
+
+        let mut buf = BytesMut::from("(1.5\r\n");
+        assert!(RespBigNumber::decode(&mut buf).is_err());
+    }
+}
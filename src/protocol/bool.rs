@@ -1,5 +1,5 @@
-use crate::protocol::{RespDecode, RespEncode, RespError};
-use bytes::BytesMut;
+use crate::protocol::{RespDecode, RespEncode, RespError, RespFrame};
+use bytes::{BufMut, BytesMut};
 
 /// #<t|f>\r\n
 impl RespDecode for bool {
@@ -7,32 +7,16 @@ impl RespDecode for bool {
     const TYPE: &'static str = "bool";
 
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        if buf.len() < 4 {
-            return Err(RespError::NotComplete);
-        }
-        Self::check_format(buf)?;
-        if buf.starts_with(b"#t\r\n") {
-            return Ok(true);
-        }
-        if buf.starts_with(b"#f\r\n") {
-            return Ok(false);
-        }
-        Err(RespError::InvalidFrame(format!(
-            "{} type false {:?}",
-            Self::TYPE,
-            buf
-        )))
-    }
-
-    fn expect_length(_buf: &[u8]) -> Result<usize, RespError> {
-        Ok(4)
+        super::decode_frame_as(buf, Self::PREFIX, |frame| match frame {
+            RespFrame::Boolean(v) => Some(v),
+            _ => None,
+        })
     }
 }
 
 impl RespEncode for bool {
-    fn encode(self) -> Vec<u8> {
-        let key = format!("#{}\r\n", if self { "t" } else { "f" });
-        key.into_bytes()
+    fn encode_to<B: BufMut>(&self, buf: &mut B) {
+        buf.put_slice(if *self { b"#t\r\n" } else { b"#f\r\n" });
     }
 }
 
@@ -53,4 +37,12 @@ mod tests {
         let decoded = bool::decode(&mut buf).unwrap();
         assert_eq!(value, decoded);
     }
+
+    #[test]
+    fn test_bool_decode_advances() {
+        let mut buf = BytesMut::from(&b"#t\r\n#f\r\n#x\r\n"[..]);
+        assert!(bool::decode(&mut buf).unwrap());
+        assert!(!bool::decode(&mut buf).unwrap());
+        assert!(bool::decode(&mut buf).is_err());
+    }
 }
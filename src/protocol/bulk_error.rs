@@ -0,0 +1,58 @@
+use bytes::{BufMut, Bytes, BytesMut};
+
+use super::{RespDecode, RespEncode, RespError, RespFrame, CRLF};
+
+/// !<length>\r\n<error>\r\n
+/// an error that may contain CR and LF, RESP3 only
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+pub struct BulkError(pub(crate) Bytes);
+
+impl RespEncode for BulkError {
+    fn encode_to<B: BufMut>(&self, buf: &mut B) {
+        super::put_prefixed_int(buf, b'!', self.0.len());
+        buf.put_slice(&self.0);
+        buf.put_slice(CRLF);
+    }
+}
+
+impl RespDecode for BulkError {
+    const PREFIX: &'static str = "!";
+    const TYPE: &'static str = "bulk_error";
+
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        super::decode_frame_as(buf, Self::PREFIX, |frame| match frame {
+            RespFrame::BulkError(v) => Some(v),
+            _ => None,
+        })
+    }
+}
+
+impl BulkError {
+    pub fn new(s: impl Into<Bytes>) -> Self {
+        BulkError(s.into())
+    }
+}
+
+impl AsRef<[u8]> for BulkError {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_bulk_error() -> Result<()> {
+        let e = BulkError::new("SYNTAX invalid\r\nsyntax");
+        let encoded = RespFrame::from(e.clone()).encode();
+        assert_eq!(encoded, b"!22\r\nSYNTAX invalid\r\nsyntax\r\n");
+
+        let mut buf = BytesMut::from(&encoded[..]);
+        assert_eq!(BulkError::decode(&mut buf)?, e);
+        assert!(buf.is_empty());
+        Ok(())
+    }
+}
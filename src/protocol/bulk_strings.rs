@@ -1,63 +1,42 @@
-use crate::protocol::{RespDecode, RespEncode, RespError};
-use bytes::{Buf, BytesMut};
+use crate::protocol::{RespDecode, RespEncode, RespError, RespFrame};
+use bytes::{BufMut, Bytes, BytesMut};
 use std::ops::Deref;
 
 const NULL: &str = "$-1\r\n";
 /// $<length>\r\n<data>\r\n
 /// $-1\r\n
+/// the payload shares the read buffer it was decoded from instead of being copied
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
-pub struct BulkString(pub(crate) Option<Vec<u8>>);
+pub struct BulkString(pub(crate) Option<Bytes>);
 
 impl RespDecode for BulkString {
     const PREFIX: &'static str = "$";
     const TYPE: &'static str = "BulkString";
 
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let is_null = super::extract_fixed_data_noadv(buf, NULL, "NullBulkString");
-        match is_null {
-            Ok(_) => {
-                buf.advance(NULL.len());
-                return Ok(BulkString::new_null());
-            }
-            Err(RespError::NotComplete) => return Err(RespError::NotComplete),
-            _ => {}
-        }
-        let (end, len) = super::parse_length(buf, Self::PREFIX)?;
-        let remained = &buf[end + super::CRLF_LEN..];
-        if remained.len() < len + super::CRLF_LEN {
-            return Err(RespError::NotComplete);
-        }
-
-        buf.advance(end + super::CRLF_LEN);
-
-        let data = buf.split_to(len + super::CRLF_LEN);
-        Ok(BulkString::new(data[..len].to_vec()))
-    }
-
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
-        let (end, len) = super::parse_length(buf, Self::PREFIX)?;
-        Ok(end + super::CRLF_LEN + len + super::CRLF_LEN)
+        super::decode_frame_as(buf, Self::PREFIX, |frame| match frame {
+            RespFrame::BulkString(s) => Some(s),
+            _ => None,
+        })
     }
 }
 
 impl RespEncode for BulkString {
-    fn encode(self) -> Vec<u8> {
-        let vec = self.0;
-        match vec {
+    fn encode_to<B: BufMut>(&self, buf: &mut B) {
+        match &self.0 {
             Some(bytes) => {
-                let length = bytes.len();
-
-                let str = String::from_utf8_lossy(bytes.as_slice());
-                let encode = format!("${}\r\n{}\r\n", length, str);
-                encode.into_bytes()
+                // the payload is written as is, it doesn't have to be UTF-8
+                super::put_prefixed_int(buf, b'$', bytes.len());
+                buf.put_slice(bytes);
+                buf.put_slice(super::CRLF);
             }
-            None => b"$-1\r\n".to_vec(),
+            None => buf.put_slice(NULL.as_bytes()),
         }
     }
 }
 
 impl BulkString {
-    pub fn new(s: impl Into<Vec<u8>>) -> Self {
+    pub fn new(s: impl Into<Bytes>) -> Self {
         BulkString(Some(s.into()))
     }
 
@@ -69,14 +48,14 @@ impl BulkString {
 impl AsRef<[u8]> for BulkString {
     fn as_ref(&self) -> &[u8] {
         match &self.0 {
-            Some(k) => k.as_slice(),
+            Some(k) => k,
             None => b"",
         }
     }
 }
 
 impl Deref for BulkString {
-    type Target = Option<Vec<u8>>;
+    type Target = Option<Bytes>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -85,25 +64,37 @@ impl Deref for BulkString {
 
 impl From<&str> for BulkString {
     fn from(s: &str) -> Self {
-        BulkString(Some(s.as_bytes().to_vec()))
+        BulkString(Some(Bytes::copy_from_slice(s.as_bytes())))
     }
 }
 
 impl From<String> for BulkString {
     fn from(s: String) -> Self {
-        BulkString(Some(s.into_bytes()))
+        BulkString(Some(s.into()))
+    }
+}
+
+impl From<Vec<u8>> for BulkString {
+    fn from(s: Vec<u8>) -> Self {
+        BulkString(Some(s.into()))
+    }
+}
+
+impl From<Bytes> for BulkString {
+    fn from(s: Bytes) -> Self {
+        BulkString(Some(s))
     }
 }
 
 impl From<&[u8]> for BulkString {
     fn from(s: &[u8]) -> Self {
-        BulkString(Some(s.to_vec()))
+        BulkString(Some(Bytes::copy_from_slice(s)))
     }
 }
 
 impl<const N: usize> From<&[u8; N]> for BulkString {
     fn from(s: &[u8; N]) -> Self {
-        BulkString(Some(s.to_vec()))
+        BulkString(Some(Bytes::copy_from_slice(s)))
     }
 }
 
@@ -128,10 +119,38 @@ mod tests {
     }
 
     #[test]
-    fn test_bulk_string_expect_length() {
-        let buf = b"$6\r\nfoobar\r\n";
-        let result = BulkString::expect_length(buf).unwrap();
-        assert_eq!(result, buf.len());
+    fn test_bulk_string_binary_roundtrip() {
+        let data = b"\xff\x00\xc3\x28\r\n".to_vec();
+        let encoded = BulkString::new(data.clone()).encode();
+        assert_eq!(encoded, b"$6\r\n\xff\x00\xc3\x28\r\n\r\n");
+
+        let mut buf = BytesMut::from(&encoded[..]);
+        assert_eq!(BulkString::decode(&mut buf).unwrap(), BulkString::new(data));
+    }
+
+    #[test]
+    fn test_bulk_string_decode_shares_buffer() {
+        let mut buf = BytesMut::from("$6\r\nfoobar\r\n+OK\r\n");
+        let start = buf.as_ptr();
+        let result = BulkString::decode(&mut buf).unwrap();
+        // the payload points into the read buffer, nothing was copied
+        assert_eq!(result.as_ref().as_ptr(), start.wrapping_add(4));
+        assert_eq!(&buf[..], b"+OK\r\n");
+    }
+
+    #[test]
+    fn test_bulk_string_decode_not_complete() {
+        let mut buf = BytesMut::from("$6\r\nfoob");
+        let result = BulkString::decode(&mut buf);
+        assert_eq!(result.unwrap_err(), RespError::NotComplete);
+        assert_eq!(&buf[..], b"$6\r\nfoob");
+    }
+
+    #[test]
+    fn test_bulk_string_decode_bad_trailer() {
+        let mut buf = BytesMut::from("$3\r\nfooXY");
+        let result = BulkString::decode(&mut buf);
+        assert!(matches!(result, Err(RespError::InvalidFrame(_))));
     }
 
     #[test]
@@ -0,0 +1,265 @@
+// byte-exact vectors from the RESP2 and RESP3 specs, every frame type must encode to its vector
+// and decode back from it
+
+use super::{
+    BulkError, BulkString, RespArray, RespAttribute, RespBigNumber, RespDecoder, RespEncode,
+    RespFrame, RespMap, RespNull, RespPush, RespSet, SimpleError, SimpleString, VerbatimString,
+};
+use anyhow::Result;
+use bytes::BytesMut;
+
+fn map(entries: Vec<(RespFrame, RespFrame)>) -> RespMap {
+    entries.into_iter().collect()
+}
+
+/// frames with their one canonical encoding
+fn vectors() -> Result<Vec<(&'static [u8], RespFrame)>> {
+    Ok(vec![
+        // RESP2
+        (b"+OK\r\n", SimpleString::new("OK").into()),
+        (b"+\r\n", SimpleString::new("").into()),
+        (b"-Error message\r\n", SimpleError::new("Error message").into()),
+        (
+            b"-ERR unknown command 'asdf'\r\n",
+            SimpleError::new("ERR unknown command 'asdf'").into(),
+        ),
+        (b":0\r\n", 0.into()),
+        (b":1\r\n", 1.into()),
+        (b":1000\r\n", 1000.into()),
+        (b":-1000\r\n", (-1000).into()),
+        (b":9223372036854775807\r\n", i64::MAX.into()),
+        (b"$5\r\nhello\r\n", b"hello".into()),
+        (b"$0\r\n\r\n", b"".into()),
+        (b"$2\r\n\r\n\r\n", b"\r\n".into()),
+        (b"$-1\r\n", BulkString::new_null().into()),
+        (b"*0\r\n", RespArray::new([]).into()),
+        (b"*-1\r\n", RespArray::new_null().into()),
+        (
+            b"*2\r\n$5\r\nhello\r\n$5\r\nworld\r\n",
+            RespArray::new([b"hello".into(), b"world".into()]).into(),
+        ),
+        (
+            b"*3\r\n:1\r\n:2\r\n:3\r\n",
+            RespArray::new([1.into(), 2.into(), 3.into()]).into(),
+        ),
+        (
+            b"*5\r\n:1\r\n:2\r\n:3\r\n:4\r\n$5\r\nhello\r\n",
+            RespArray::new([1.into(), 2.into(), 3.into(), 4.into(), b"hello".into()]).into(),
+        ),
+        (
+            b"*2\r\n*3\r\n:1\r\n:2\r\n:3\r\n*2\r\n+Hello\r\n-World\r\n",
+            RespArray::new([
+                RespArray::new([1.into(), 2.into(), 3.into()]).into(),
+                RespArray::new([
+                    SimpleString::new("Hello").into(),
+                    SimpleError::new("World").into(),
+                ])
+                .into(),
+            ])
+            .into(),
+        ),
+        (
+            b"*3\r\n$5\r\nhello\r\n$-1\r\n$5\r\nworld\r\n",
+            RespArray::new([
+                b"hello".into(),
+                BulkString::new_null().into(),
+                b"world".into(),
+            ])
+            .into(),
+        ),
+        // RESP3
+        (b"_\r\n", RespNull.into()),
+        (b"#t\r\n", true.into()),
+        (b"#f\r\n", false.into()),
+        (b",1.23\r\n", 1.23.into()),
+        (b",-1.5\r\n", (-1.5).into()),
+        (b",10\r\n", 10.0.into()),
+        (b",0.0001\r\n", 0.0001.into()),
+        (b",inf\r\n", f64::INFINITY.into()),
+        (b",-inf\r\n", f64::NEG_INFINITY.into()),
+        (b",nan\r\n", f64::NAN.into()),
+        (
+            b"(3492890328409238509324850943850943825024385\r\n",
+            RespBigNumber::new("3492890328409238509324850943850943825024385")?.into(),
+        ),
+        (b"(-12\r\n", RespBigNumber::new("-12")?.into()),
+        (
+            b"!21\r\nSYNTAX invalid syntax\r\n",
+            BulkError::new("SYNTAX invalid syntax").into(),
+        ),
+        (
+            b"=15\r\ntxt:Some string\r\n",
+            VerbatimString::txt("Some string").into(),
+        ),
+        (
+            b"%2\r\n+first\r\n:1\r\n+second\r\n:2\r\n",
+            map(vec![("first".into(), 1.into()), ("second".into(), 2.into())]).into(),
+        ),
+        (b"%0\r\n", RespMap::new().into()),
+        (
+            b"~5\r\n+orange\r\n+apple\r\n#t\r\n:100\r\n:999\r\n",
+            RespSet::new([
+                "orange".into(),
+                "apple".into(),
+                true.into(),
+                100.into(),
+                999.into(),
+            ])
+            .into(),
+        ),
+        (
+            b">3\r\n$7\r\nmessage\r\n$7\r\nchannel\r\n$5\r\nhello\r\n",
+            RespPush::new([b"message".into(), b"channel".into(), b"hello".into()]).into(),
+        ),
+        (
+            b"|1\r\n+key-popularity\r\n%2\r\n$1\r\na\r\n,0.1923\r\n$1\r\nb\r\n,0.0012\r\n*2\r\n:2039123\r\n:9543892\r\n",
+            RespAttribute::new(
+                map(vec![(
+                    "key-popularity".into(),
+                    map(vec![
+                        (b"a".into(), 0.1923.into()),
+                        (b"b".into(), 0.0012.into()),
+                    ])
+                    .into(),
+                )]),
+                RespArray::new([2039123.into(), 9543892.into()]),
+            )
+            .into(),
+        ),
+    ])
+}
+
+/// NaN is not equal to itself, frames are compared through their encoding
+fn assert_same(frame: &RespFrame, expected: &RespFrame) {
+    assert_eq!(
+        frame.encode(),
+        expected.encode(),
+        "{:?} != {:?}",
+        frame,
+        expected
+    );
+    assert!(frame == expected || matches!(frame, RespFrame::Double(d) if d.is_nan()));
+}
+
+#[test]
+fn test_encode_vectors() -> Result<()> {
+    for (bytes, frame) in vectors()? {
+        assert_eq!(
+            frame.encode(),
+            bytes,
+            "{:?} encoded as {:?}",
+            frame,
+            String::from_utf8_lossy(&frame.encode())
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_decode_vectors() -> Result<()> {
+    for (bytes, expected) in vectors()? {
+        let mut buf = BytesMut::from(bytes);
+        buf.extend_from_slice(b"+next\r\n");
+        let frame = RespDecoder::new().decode(&mut buf)?;
+        assert_same(&frame.expect("a complete frame"), &expected);
+        // exactly the frame is consumed
+        assert_eq!(&buf[..], b"+next\r\n", "decoding {:?}", expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_decode_vectors_split() -> Result<()> {
+    for (bytes, expected) in vectors()? {
+        let mut decoder = RespDecoder::new();
+        let mut buf = BytesMut::new();
+        let mut frame = None;
+        for (i, b) in bytes.iter().enumerate() {
+            assert!(frame.is_none(), "{:?} decoded before byte {}", expected, i);
+            buf.extend_from_slice(&[*b]);
+            frame = decoder.decode(&mut buf)?;
+        }
+        assert_same(&frame.expect("a complete frame"), &expected);
+        assert!(buf.is_empty());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_decode_alternative_forms() -> Result<()> {
+    // forms the spec allows but redis never sends
+    let vectors: Vec<(&[u8], RespFrame)> = vec![
+        (b":+1000\r\n", 1000.into()),
+        (b":-0\r\n", 0.into()),
+        (b",+1.23\r\n", 1.23.into()),
+        (b",1.23E+4\r\n", 12300.0.into()),
+        (b",1.5e-3\r\n", 0.0015.into()),
+        (b",+inf\r\n", f64::INFINITY.into()),
+    ];
+    for (bytes, expected) in vectors {
+        let mut buf = BytesMut::from(bytes);
+        let frame = RespDecoder::new().decode(&mut buf)?;
+        assert_eq!(frame, Some(expected));
+        assert!(buf.is_empty());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_decode_invalid() {
+    let vectors: &[&[u8]] = &[
+        b"#x\r\n",
+        b"#\r\n",
+        b"_x\r\n",
+        b":1.5\r\n",
+        b":\r\n",
+        b",abc\r\n",
+        b"(1.5\r\n",
+        b"(\r\n",
+        b"$-2\r\n",
+        b"$3\r\nhello\r\n",
+        b"=3\r\ntxt\r\n",
+        b"~-1\r\n",
+        b"%-1\r\n",
+        b"?\r\n",
+    ];
+    for bytes in vectors {
+        let mut buf = BytesMut::from(*bytes);
+        assert!(
+            RespDecoder::new().decode(&mut buf).is_err(),
+            "{:?} should be rejected",
+            String::from_utf8_lossy(bytes)
+        );
+    }
+}
+
+#[test]
+fn test_resp2_downgrade_vectors() -> Result<()> {
+    let vectors: Vec<(RespFrame, &[u8])> = vec![
+        (RespNull.into(), b"$-1\r\n"),
+        (true.into(), b":1\r\n"),
+        (false.into(), b":0\r\n"),
+        (1.5.into(), b"$3\r\n1.5\r\n"),
+        (f64::INFINITY.into(), b"$3\r\ninf\r\n"),
+        (RespBigNumber::new("-12")?.into(), b"$3\r\n-12\r\n"),
+        (VerbatimString::txt("hi").into(), b"$2\r\nhi\r\n"),
+        (BulkError::new("ERR a\r\nb").into(), b"-ERR a  b\r\n"),
+        (
+            map(vec![("a".into(), 1.into())]).into(),
+            b"*2\r\n$1\r\na\r\n:1\r\n",
+        ),
+        (RespSet::new([1.into()]).into(), b"*1\r\n:1\r\n"),
+        (
+            RespPush::new([b"message".into()]).into(),
+            b"*1\r\n$7\r\nmessage\r\n",
+        ),
+        (
+            RespAttribute::new(map(vec![("ttl".into(), 1.into())]), RespNull).into(),
+            b"$-1\r\n",
+        ),
+    ];
+    for (frame, bytes) in vectors {
+        assert_eq!(frame.clone().into_resp2().encode(), bytes, "{:?}", frame);
+    }
+    Ok(())
+}
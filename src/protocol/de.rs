@@ -0,0 +1,518 @@
+use bytes::{Bytes, BytesMut};
+use serde::de::{self, DeserializeOwned, Visitor};
+
+use super::{
+    BulkString, RespArray, RespDecoder, RespError, RespFrame, RespMap, RespNull, RespPush, RespSet,
+    SerdeError, SimpleString,
+};
+
+/// convert a frame into a value, the inverse of `to_frame`. Replies of RESP2 clients are
+/// accepted too: maps may be flat arrays of keys and values, and numbers or booleans may be
+/// strings or integers. An error frame fails with `SerdeError::ErrorReply`.
+pub fn from_frame<T: DeserializeOwned>(frame: RespFrame) -> Result<T, SerdeError> {
+    T::deserialize(FrameDeserializer(frame))
+}
+
+/// decode a single frame and convert it into a value, see `from_frame`
+pub fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, SerdeError> {
+    let mut buf = BytesMut::from(bytes);
+    let frame = RespDecoder::new()
+        .decode(&mut buf)?
+        .ok_or(RespError::NotComplete)?;
+    if !buf.is_empty() {
+        return Err(SerdeError::TrailingBytes(buf.len()));
+    }
+    from_frame(frame)
+}
+
+struct FrameDeserializer(RespFrame);
+
+impl FrameDeserializer {
+    /// the frame without attributes, error replies fail here
+    fn into_frame(self) -> Result<RespFrame, SerdeError> {
+        match self.0 {
+            RespFrame::Attribute(a) => FrameDeserializer(a.into_frame()).into_frame(),
+            RespFrame::Error(e) => Err(SerdeError::ErrorReply(e.0)),
+            RespFrame::BulkError(e) => Err(SerdeError::ErrorReply(
+                String::from_utf8_lossy(&e.0).into_owned(),
+            )),
+            frame => Ok(frame),
+        }
+    }
+
+    /// the text of a string frame, numbers in RESP2 replies are sent this way
+    fn text(frame: &RespFrame) -> Option<&[u8]> {
+        match frame {
+            RespFrame::SimpleString(s) => Some(s),
+            RespFrame::BulkString(BulkString(Some(s))) => Some(s),
+            RespFrame::VerbatimString(s) => Some(&s.data),
+            RespFrame::BigNumber(n) => Some(n.0.as_bytes()),
+            _ => None,
+        }
+    }
+
+    fn parse<T: std::str::FromStr>(frame: &RespFrame, expected: &str) -> Result<T, SerdeError> {
+        Self::text(frame)
+            .and_then(|s| std::str::from_utf8(s).ok())
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| unexpected(frame, expected))
+    }
+}
+
+fn unexpected(frame: &RespFrame, expected: &str) -> SerdeError {
+    SerdeError::Message(format!("invalid type: {:?}, expected {}", frame, expected))
+}
+
+fn is_null(frame: &RespFrame) -> bool {
+    matches!(
+        frame,
+        RespFrame::Null(RespNull)
+            | RespFrame::BulkString(BulkString(None))
+            | RespFrame::Array(RespArray(None))
+    )
+}
+
+fn visit_bytes<'de, V: Visitor<'de>>(bytes: Bytes, visitor: V) -> Result<V::Value, SerdeError> {
+    match String::from_utf8(bytes.to_vec()) {
+        Ok(s) => visitor.visit_string(s),
+        Err(e) => visitor.visit_byte_buf(e.into_bytes()),
+    }
+}
+
+/// integers are taken from integer frames or parsed from strings
+macro_rules! deserialize_number {
+    ($($method:ident => $visit:ident($ty:ty)),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+                match self.into_frame()? {
+                    RespFrame::Integer(n) => visitor.visit_i64(n),
+                    RespFrame::Double(d) => visitor.visit_f64(d),
+                    frame => visitor.$visit(Self::parse::<$ty>(&frame, stringify!($ty))?),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for FrameDeserializer {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.into_frame()? {
+            frame if is_null(&frame) => visitor.visit_unit(),
+            RespFrame::SimpleString(SimpleString(s)) => visit_bytes(s, visitor),
+            RespFrame::BulkString(BulkString(Some(s))) => visit_bytes(s, visitor),
+            RespFrame::VerbatimString(s) => visit_bytes(s.data, visitor),
+            RespFrame::Integer(n) => visitor.visit_i64(n),
+            RespFrame::Double(d) => visitor.visit_f64(d),
+            RespFrame::Boolean(b) => visitor.visit_bool(b),
+            RespFrame::BigNumber(n) => match n.0.parse::<i128>() {
+                Ok(n) => visitor.visit_i128(n),
+                Err(_) => visitor.visit_string(n.0),
+            },
+            RespFrame::Array(RespArray(Some(items)))
+            | RespFrame::Set(RespSet(items))
+            | RespFrame::Push(RespPush(items)) => visitor.visit_seq(SeqAccess(items.into_iter())),
+            RespFrame::Map(map) => visitor.visit_map(MapAccess::new(map)),
+            frame => Err(unexpected(&frame, "a value")),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.into_frame()? {
+            RespFrame::Boolean(b) => visitor.visit_bool(b),
+            // RESP2 replies send booleans as 0 and 1
+            RespFrame::Integer(n @ (0 | 1)) => visitor.visit_bool(n == 1),
+            frame => match Self::text(&frame) {
+                Some(b"1") => visitor.visit_bool(true),
+                Some(b"0") => visitor.visit_bool(false),
+                _ => Err(unexpected(&frame, "a boolean")),
+            },
+        }
+    }
+
+    deserialize_number!(
+        deserialize_i8 => visit_i64(i64),
+        deserialize_i16 => visit_i64(i64),
+        deserialize_i32 => visit_i64(i64),
+        deserialize_i64 => visit_i64(i64),
+        deserialize_i128 => visit_i128(i128),
+        deserialize_u8 => visit_u64(u64),
+        deserialize_u16 => visit_u64(u64),
+        deserialize_u32 => visit_u64(u64),
+        deserialize_u64 => visit_u64(u64),
+        deserialize_u128 => visit_u128(u128),
+        deserialize_f32 => visit_f64(f64),
+        deserialize_f64 => visit_f64(f64),
+    );
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.into_frame()? {
+            RespFrame::SimpleString(SimpleString(s))
+            | RespFrame::BulkString(BulkString(Some(s))) => visitor.visit_byte_buf(s.to_vec()),
+            RespFrame::VerbatimString(s) => visitor.visit_byte_buf(s.data.to_vec()),
+            frame => FrameDeserializer(frame).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.into_frame()? {
+            frame if is_null(&frame) => visitor.visit_none(),
+            frame => visitor.visit_some(FrameDeserializer(frame)),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.into_frame()? {
+            frame if is_null(&frame) => visitor.visit_unit(),
+            frame => Err(unexpected(&frame, "null")),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.into_frame()? {
+            RespFrame::Map(map) => visitor.visit_map(MapAccess::new(map)),
+            // RESP2 has no maps, HGETALL and friends reply with keys and values in turn
+            RespFrame::Array(RespArray(Some(items))) if items.len().is_multiple_of(2) => {
+                let mut map = RespMap::with_capacity(items.len() / 2);
+                let mut items = items.into_iter();
+                while let (Some(k), Some(v)) = (items.next(), items.next()) {
+                    map.push(k, v);
+                }
+                visitor.visit_map(MapAccess::new(map))
+            }
+            frame => Err(unexpected(&frame, "a map")),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.into_frame()? {
+            RespFrame::Map(map) if map.len() == 1 => {
+                let (variant, value) = map.0.into_iter().next().expect("one entry");
+                visitor.visit_enum(EnumAccess {
+                    variant,
+                    value: Some(value),
+                })
+            }
+            // the same map flattened for RESP2
+            RespFrame::Array(RespArray(Some(items))) if items.len() == 2 => {
+                let mut items = items.into_iter();
+                visitor.visit_enum(EnumAccess {
+                    variant: items.next().expect("two items"),
+                    value: items.next(),
+                })
+            }
+            frame if Self::text(&frame).is_some() => visitor.visit_enum(EnumAccess {
+                variant: frame,
+                value: None,
+            }),
+            frame => Err(unexpected(&frame, "an enum")),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string seq tuple tuple_struct identifier
+    }
+}
+
+struct SeqAccess(std::vec::IntoIter<RespFrame>);
+
+impl<'de> de::SeqAccess<'de> for SeqAccess {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SerdeError> {
+        self.0
+            .next()
+            .map(|frame| seed.deserialize(FrameDeserializer(frame)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+struct MapAccess {
+    entries: std::vec::IntoIter<(RespFrame, RespFrame)>,
+    value: Option<RespFrame>,
+}
+
+impl MapAccess {
+    fn new(map: RespMap) -> Self {
+        Self {
+            entries: map.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeError> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.value = Some(value);
+        seed.deserialize(FrameDeserializer(key)).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerdeError> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| SerdeError::Message("map value without a key".to_string()))?;
+        seed.deserialize(FrameDeserializer(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// a unit variant is its name, any other variant a map of its name to its data
+struct EnumAccess {
+    variant: RespFrame,
+    value: Option<RespFrame>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess {
+    type Error = SerdeError;
+    type Variant = VariantAccess;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantAccess), SerdeError> {
+        let variant = seed.deserialize(FrameDeserializer(self.variant))?;
+        Ok((variant, VariantAccess(self.value)))
+    }
+}
+
+struct VariantAccess(Option<RespFrame>);
+
+impl VariantAccess {
+    fn value(self) -> Result<FrameDeserializer, SerdeError> {
+        self.0
+            .map(FrameDeserializer)
+            .ok_or_else(|| SerdeError::Message("expected an enum variant with data".to_string()))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantAccess {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        match self.0 {
+            None => Ok(()),
+            Some(frame) if is_null(&frame) => Ok(()),
+            Some(frame) => Err(unexpected(&frame, "a unit variant")),
+        }
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        seed.deserialize(self.value()?)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        de::Deserializer::deserialize_seq(self.value()?, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        de::Deserializer::deserialize_map(self.value()?, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{to_bytes, to_frame, RespAttribute, RespBigNumber, SimpleError};
+    use anyhow::Result;
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct User {
+        name: String,
+        age: u32,
+        email: Option<String>,
+        tags: Vec<String>,
+        #[serde(with = "serde_bytes_compat")]
+        avatar: Vec<u8>,
+        role: Role,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Role {
+        Admin,
+        Guest { until: i64 },
+        Custom(String),
+    }
+
+    /// store bytes as a bulk string rather than an array of integers
+    mod serde_bytes_compat {
+        use serde::{Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(v: &[u8], s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_bytes(v)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+            struct BytesVisitor;
+            impl serde::de::Visitor<'_> for BytesVisitor {
+                type Value = Vec<u8>;
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("bytes")
+                }
+                fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+                    Ok(v)
+                }
+            }
+            d.deserialize_byte_buf(BytesVisitor)
+        }
+    }
+
+    fn users() -> Vec<User> {
+        vec![
+            User {
+                name: "alice".to_string(),
+                age: 30,
+                email: Some("a@example.com".to_string()),
+                tags: vec!["x".to_string()],
+                avatar: vec![0xff, 0, 1],
+                role: Role::Admin,
+            },
+            User {
+                name: "bob".to_string(),
+                age: 7,
+                email: None,
+                tags: vec![],
+                avatar: vec![],
+                role: Role::Guest { until: -1 },
+            },
+            User {
+                name: "carol".to_string(),
+                age: 1,
+                email: None,
+                tags: vec![],
+                avatar: b"png".to_vec(),
+                role: Role::Custom("ops".to_string()),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_roundtrip() -> Result<()> {
+        for user in users() {
+            assert_eq!(from_frame::<User>(to_frame(&user)?)?, user);
+            assert_eq!(from_bytes::<User>(&to_bytes(&user)?)?, user);
+            // what a RESP2 client receives reads back the same
+            assert_eq!(from_frame::<User>(to_frame(&user)?.into_resp2())?, user);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_frame_values() -> Result<()> {
+        assert_eq!(from_frame::<i64>(b"42".into())?, 42);
+        assert_eq!(from_frame::<f64>(b"1.5".into())?, 1.5);
+        assert!(from_frame::<bool>(1.into())?);
+        assert_eq!(from_frame::<Option<String>>(RespNull.into())?, None);
+        assert_eq!(
+            from_frame::<Option<String>>(BulkString::new_null().into())?,
+            None
+        );
+        assert_eq!(
+            from_frame::<u64>(RespBigNumber::new("18446744073709551615")?.into())?,
+            u64::MAX
+        );
+        assert_eq!(
+            from_frame::<Vec<i64>>(RespSet::new([1.into(), 2.into()]).into())?,
+            vec![1, 2]
+        );
+
+        let mut map = RespMap::new();
+        map.insert(b"a", 1);
+        let frame = RespAttribute::new(RespMap::new(), map).into();
+        assert_eq!(
+            from_frame::<HashMap<String, i64>>(frame)?,
+            HashMap::from([("a".to_string(), 1)])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_frame_errors() {
+        let err = from_frame::<i64>(SimpleError::new("ERR boom").into()).unwrap_err();
+        assert_eq!(err, SerdeError::ErrorReply("ERR boom".to_string()));
+
+        assert!(from_frame::<i64>(b"abc".into()).is_err());
+        assert!(from_frame::<String>(RespArray::new([]).into()).is_err());
+        assert_eq!(
+            from_bytes::<i64>(b":1\r\n:2\r\n").unwrap_err(),
+            SerdeError::TrailingBytes(4)
+        );
+        assert_eq!(
+            from_bytes::<i64>(b":1").unwrap_err(),
+            SerdeError::Resp(RespError::NotComplete)
+        );
+    }
+}
@@ -0,0 +1,612 @@
+use bytes::{Bytes, BytesMut};
+use std::ops::Range;
+
+use super::{
+    BulkError, BulkString, RespArray, RespAttribute, RespBigNumber, RespError, RespFrame, RespMap,
+    RespNull, RespPush, RespSet, SimpleError, SimpleString, VerbatimString, CRLF, CRLF_LEN,
+};
+
+/// elements reserved up front for an aggregate, the declared length can't be trusted
+const MAX_PREALLOC: usize = 1024;
+
+/// bounds on what a client may send, a frame exceeding one is a protocol error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RespLimits {
+    /// longest bulk string, bulk error or verbatim string, `proto-max-bulk-len`
+    pub max_bulk_len: usize,
+    /// most elements in an aggregate, a map counts its pairs
+    pub max_multibulk_len: usize,
+    /// most aggregates nested in each other
+    pub max_nesting_depth: usize,
+    /// longest line without its CRLF, either an inline command or the header of a frame
+    pub max_inline_len: usize,
+}
+
+impl Default for RespLimits {
+    fn default() -> Self {
+        // the same as redis, except nesting that redis never needs for requests
+        Self {
+            max_bulk_len: 512 * 1024 * 1024,
+            max_multibulk_len: i32::MAX as usize,
+            max_nesting_depth: 128,
+            max_inline_len: 64 * 1024,
+        }
+    }
+}
+
+/// a RESP decoder that keeps its progress between reads.
+///
+/// Every byte is parsed once: the decoder remembers where it stopped and which aggregates are
+/// still waiting for elements. The buffer is only consumed once a whole frame is decoded, string
+/// payloads are then sliced out of it without copying.
+#[derive(Debug, Default)]
+pub struct RespDecoder {
+    /// end of the parsed part of the buffer, the current frame starts at 0
+    pos: usize,
+    /// where the search for the next CRLF resumes
+    scanned: usize,
+    /// aggregates still waiting for elements, innermost last
+    stack: Vec<Aggregate>,
+    limits: RespLimits,
+}
+
+#[derive(Debug)]
+struct Aggregate {
+    kind: AggregateKind,
+    /// number of elements, a map counts keys and values and an attribute also counts the frame
+    /// it is attached to
+    len: usize,
+    items: Vec<Node>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum AggregateKind {
+    Array,
+    Set,
+    Map,
+    Push,
+    Attribute,
+}
+
+/// a decoded value whose strings are still ranges of the read buffer
+#[derive(Debug)]
+enum Node {
+    Simple(Range<usize>),
+    Bulk(Range<usize>),
+    BulkError(Range<usize>),
+    /// the format, then the data
+    Verbatim([u8; 3], Range<usize>),
+    Array(Vec<Node>),
+    Set(Vec<Node>),
+    Map(Vec<Node>),
+    Push(Vec<Node>),
+    /// the attribute keys and values, then the frame they describe
+    Attribute(Vec<Node>),
+    Frame(RespFrame),
+}
+
+#[derive(Debug)]
+enum Token {
+    Node(Node),
+    Open(AggregateKind, usize),
+}
+
+impl RespDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_limits(limits: RespLimits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    pub fn limits(&self) -> &RespLimits {
+        &self.limits
+    }
+
+    /// true between frames, when the next byte of the buffer starts a new frame
+    pub fn is_idle(&self) -> bool {
+        self.pos == 0 && self.stack.is_empty()
+    }
+
+    /// decode the next frame, `None` if the buffer doesn't hold all of it yet. The same buffer
+    /// must be passed again once more data is appended to it.
+    pub fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<RespFrame>, RespError> {
+        let ret = self.decode_frame(buf);
+        if ret.is_err() {
+            // the stream can't be resynchronized, start over if the caller goes on anyway
+            *self = Self::with_limits(self.limits);
+        }
+        ret
+    }
+
+    fn decode_frame(&mut self, buf: &mut BytesMut) -> Result<Option<RespFrame>, RespError> {
+        loop {
+            let Some(token) = self.next_token(buf)? else {
+                return Ok(None);
+            };
+            if matches!(token, Token::Open(..)) && self.stack.len() >= self.limits.max_nesting_depth
+            {
+                return Err(RespError::LimitExceeded(
+                    "too many nested aggregates".to_string(),
+                ));
+            }
+            let mut node = match token {
+                Token::Open(kind, len) if len > 0 => {
+                    self.stack.push(Aggregate {
+                        kind,
+                        len,
+                        items: Vec::with_capacity(len.min(MAX_PREALLOC)),
+                    });
+                    continue;
+                }
+                Token::Open(kind, _) => Aggregate {
+                    kind,
+                    len: 0,
+                    items: Vec::new(),
+                }
+                .into_node(),
+                Token::Node(node) => node,
+            };
+
+            // attach the node to its parent, closing every aggregate it completes
+            loop {
+                let Some(parent) = self.stack.last_mut() else {
+                    let data = buf.split_to(self.pos).freeze();
+                    self.pos = 0;
+                    self.scanned = 0;
+                    return node.into_frame(&data).map(Some);
+                };
+                parent.items.push(node);
+                if parent.items.len() < parent.len {
+                    break;
+                }
+                node = match self.stack.pop() {
+                    Some(aggregate) => aggregate.into_node(),
+                    None => unreachable!("the parent was just checked"),
+                };
+            }
+        }
+    }
+
+    /// parse the element starting at `pos`, `None` if it is not complete
+    fn next_token(&mut self, buf: &BytesMut) -> Result<Option<Token>, RespError> {
+        let Some(&prefix) = buf.get(self.pos) else {
+            return Ok(None);
+        };
+        if !super::is_resp_prefix(prefix) {
+            return Err(RespError::InvalidFrameType(format!(
+                "unknown frame type: {:?}",
+                prefix as char
+            )));
+        }
+        let Some(line_end) = self.find_crlf(buf) else {
+            if buf.len() - self.pos > self.limits.max_inline_len + 1 {
+                return Err(RespError::LimitExceeded("too big line".to_string()));
+            }
+            return Ok(None);
+        };
+        if line_end - self.pos > self.limits.max_inline_len + 1 {
+            return Err(RespError::LimitExceeded("too big line".to_string()));
+        }
+        let line = self.pos + 1..line_end;
+        let next = line_end + CRLF_LEN;
+
+        let token = match prefix {
+            b'+' => Token::Node(Node::Simple(line)),
+            b'-' => Token::Node(Node::Frame(
+                SimpleError::new(String::from_utf8_lossy(&buf[line])).into(),
+            )),
+            b':' => {
+                let n: i64 = String::from_utf8_lossy(&buf[line]).parse()?;
+                Token::Node(Node::Frame(n.into()))
+            }
+            b',' => {
+                let d: f64 = String::from_utf8_lossy(&buf[line]).parse()?;
+                Token::Node(Node::Frame(d.into()))
+            }
+            b'#' => match &buf[line] {
+                b"t" => Token::Node(Node::Frame(true.into())),
+                b"f" => Token::Node(Node::Frame(false.into())),
+                data => {
+                    return Err(RespError::InvalidFrame(format!(
+                        "bool type false {:?}",
+                        String::from_utf8_lossy(data)
+                    )))
+                }
+            },
+            b'_' if line.is_empty() => Token::Node(Node::Frame(RespNull.into())),
+            b'_' => return Err(RespError::InvalidFrame("Invalid null".to_string())),
+            b'$' => match parse_length(&buf[line])? {
+                None => Token::Node(Node::Frame(BulkString::new_null().into())),
+                Some(len) => {
+                    return Ok(self.blob(buf, next, len)?.map(Node::Bulk).map(Token::Node))
+                }
+            },
+            b'!' => match parse_length(&buf[line])? {
+                Some(len) => {
+                    return Ok(self
+                        .blob(buf, next, len)?
+                        .map(Node::BulkError)
+                        .map(Token::Node))
+                }
+                None => {
+                    return Err(RespError::InvalidFrame(
+                        "Invalid bulk error length".to_string(),
+                    ))
+                }
+            },
+            b'=' => match parse_length(&buf[line])? {
+                Some(len) if len >= 4 => {
+                    let Some(range) = self.blob(buf, next, len)? else {
+                        return Ok(None);
+                    };
+                    let data = &buf[range.clone()];
+                    if data[3] != b':' {
+                        return Err(RespError::InvalidFrame(
+                            "verbatim string has no format".to_string(),
+                        ));
+                    }
+                    let format = [data[0], data[1], data[2]];
+                    return Ok(Some(Token::Node(Node::Verbatim(
+                        format,
+                        range.start + 4..range.end,
+                    ))));
+                }
+                _ => {
+                    return Err(RespError::InvalidFrame(
+                        "Invalid verbatim string length".to_string(),
+                    ))
+                }
+            },
+            b'(' => {
+                let n = RespBigNumber::new(String::from_utf8_lossy(&buf[line]))?;
+                Token::Node(Node::Frame(n.into()))
+            }
+            b'*' => match parse_length(&buf[line])? {
+                None => Token::Node(Node::Frame(RespArray::new_null().into())),
+                Some(len) => Token::Open(AggregateKind::Array, self.multibulk_len(len)?),
+            },
+            b'~' => match parse_length(&buf[line])? {
+                Some(len) => Token::Open(AggregateKind::Set, self.multibulk_len(len)?),
+                None => return Err(RespError::InvalidFrame("Invalid set length".to_string())),
+            },
+            b'%' => match parse_length(&buf[line])? {
+                Some(len) => match self.multibulk_len(len)?.checked_mul(2) {
+                    Some(len) => Token::Open(AggregateKind::Map, len),
+                    None => return Err(RespError::InvalidFrame("Invalid map length".to_string())),
+                },
+                None => return Err(RespError::InvalidFrame("Invalid map length".to_string())),
+            },
+            b'>' => match parse_length(&buf[line])? {
+                Some(len) => Token::Open(AggregateKind::Push, self.multibulk_len(len)?),
+                None => return Err(RespError::InvalidFrame("Invalid push length".to_string())),
+            },
+            b'|' => match parse_length(&buf[line])?
+                .map(|len| self.multibulk_len(len))
+                .transpose()?
+                .and_then(|len| len.checked_mul(2))
+            {
+                Some(len) => Token::Open(AggregateKind::Attribute, len + 1),
+                None => {
+                    return Err(RespError::InvalidFrame(
+                        "Invalid attribute length".to_string(),
+                    ))
+                }
+            },
+            _ => {
+                return Err(RespError::InvalidFrameType(format!(
+                    "unknown frame type: {:?}",
+                    prefix as char
+                )))
+            }
+        };
+        self.advance(next);
+        Ok(Some(token))
+    }
+
+    /// the payload of a length prefixed string whose header ends at `start`, `None` if it is not
+    /// complete. The header is parsed again once the payload arrived, the payload is never
+    /// scanned.
+    fn blob(
+        &mut self,
+        buf: &[u8],
+        start: usize,
+        len: usize,
+    ) -> Result<Option<Range<usize>>, RespError> {
+        if len > self.limits.max_bulk_len {
+            return Err(RespError::LimitExceeded("invalid bulk length".to_string()));
+        }
+        let end = start
+            .checked_add(len)
+            .ok_or_else(|| RespError::InvalidFrame("Invalid length".to_string()))?;
+        if buf.len().saturating_sub(CRLF_LEN) < end {
+            return Ok(None);
+        }
+        if &buf[end..end + CRLF_LEN] != CRLF {
+            return Err(RespError::InvalidFrame(
+                "string payload is not terminated by CRLF".to_string(),
+            ));
+        }
+        self.advance(end + CRLF_LEN);
+        Ok(Some(start..end))
+    }
+
+    fn multibulk_len(&self, len: usize) -> Result<usize, RespError> {
+        if len > self.limits.max_multibulk_len {
+            return Err(RespError::LimitExceeded(
+                "invalid multibulk length".to_string(),
+            ));
+        }
+        Ok(len)
+    }
+
+    /// find the CRLF ending the line at `pos`, without scanning the same bytes twice
+    fn find_crlf(&mut self, buf: &[u8]) -> Option<usize> {
+        let from = self.scanned.max(self.pos);
+        match buf[from..].windows(CRLF_LEN).position(|w| w == CRLF) {
+            Some(i) => Some(from + i),
+            None => {
+                // the last byte may be the CR of a CRLF split between two reads
+                self.scanned = buf.len().saturating_sub(1).max(from);
+                None
+            }
+        }
+    }
+
+    fn advance(&mut self, pos: usize) {
+        self.pos = pos;
+        self.scanned = pos;
+    }
+}
+
+impl Aggregate {
+    fn into_node(self) -> Node {
+        match self.kind {
+            AggregateKind::Array => Node::Array(self.items),
+            AggregateKind::Set => Node::Set(self.items),
+            AggregateKind::Map => Node::Map(self.items),
+            AggregateKind::Push => Node::Push(self.items),
+            AggregateKind::Attribute => Node::Attribute(self.items),
+        }
+    }
+}
+
+impl Node {
+    fn into_frame(self, data: &Bytes) -> Result<RespFrame, RespError> {
+        let frames = |items: Vec<Node>| {
+            items
+                .into_iter()
+                .map(|node| node.into_frame(data))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(match self {
+            Node::Simple(range) => SimpleString::new(data.slice(range)).into(),
+            Node::Bulk(range) => BulkString::new(data.slice(range)).into(),
+            Node::BulkError(range) => BulkError::new(data.slice(range)).into(),
+            Node::Verbatim(format, range) => VerbatimString::new(format, data.slice(range)).into(),
+            Node::Array(items) => RespArray::new(frames(items)?).into(),
+            Node::Set(items) => RespSet::new(frames(items)?).into(),
+            Node::Map(items) => into_map(frames(items)?).into(),
+            Node::Push(items) => RespPush::new(frames(items)?).into(),
+            Node::Attribute(items) => {
+                let mut items = frames(items)?;
+                let frame = items.pop().ok_or_else(|| {
+                    RespError::InvalidFrame("attribute without a frame".to_string())
+                })?;
+                RespAttribute::new(into_map(items), frame).into()
+            }
+            Node::Frame(frame) => frame,
+        })
+    }
+}
+
+/// build a map from alternating keys and values, kept in the order they were sent
+fn into_map(items: Vec<RespFrame>) -> RespMap {
+    let mut map = RespMap::with_capacity(items.len() / 2);
+    let mut items = items.into_iter();
+    while let (Some(key), Some(value)) = (items.next(), items.next()) {
+        map.push(key, value);
+    }
+    map
+}
+
+/// parse the length of a bulk string or an aggregate, `None` for the -1 of a null
+fn parse_length(line: &[u8]) -> Result<Option<usize>, RespError> {
+    if line == b"-1" {
+        return Ok(None);
+    }
+    let s = String::from_utf8_lossy(line);
+    s.parse()
+        .map(Some)
+        .map_err(|_| RespError::InvalidFrame(format!("Invalid length: {:?}", s)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_decoder_nested() -> Result<()> {
+        let mut buf = BytesMut::from(
+            &b"*4\r\n:1\r\n~2\r\n+a\r\n#t\r\n%1\r\n+k\r\n*2\r\n,1.5\r\n_\r\n$-1\r\n*0\r\n"[..],
+        );
+        let frame = RespDecoder::new().decode(&mut buf)?;
+
+        let mut map = RespMap::new();
+        map.insert("k", RespArray::new([1.5.into(), RespNull.into()]));
+        let expected: RespFrame = RespArray::new([
+            1.into(),
+            RespSet::new([SimpleString::new("a").into(), true.into()]).into(),
+            map.into(),
+            BulkString::new_null().into(),
+        ])
+        .into();
+        assert_eq!(frame, Some(expected));
+        assert_eq!(&buf[..], b"*0\r\n");
+        assert_eq!(
+            RespDecoder::new().decode(&mut buf)?,
+            Some(RespArray::new([]).into())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_decoder_resp3_types() -> Result<()> {
+        let mut buf = BytesMut::from(
+            &b"*4\r\n>2\r\n+a\r\n(-12345678901234567890\r\n=7\r\ntxt:abc\r\n!5\r\nERR x\r\n|1\r\n+ttl\r\n:3\r\n+v\r\n"[..],
+        );
+        let frame = RespDecoder::new().decode(&mut buf)?;
+
+        let mut attrs = RespMap::new();
+        attrs.insert("ttl", 3);
+        let expected: RespFrame = RespArray::new([
+            RespPush::new([
+                SimpleString::new("a").into(),
+                RespBigNumber::new("-12345678901234567890")?.into(),
+            ])
+            .into(),
+            VerbatimString::txt("abc").into(),
+            BulkError::new("ERR x").into(),
+            RespAttribute::new(attrs, SimpleString::new("v")).into(),
+        ])
+        .into();
+        assert_eq!(frame, Some(expected));
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from(&b"=3\r\ntxt\r\n"[..]);
+        assert!(RespDecoder::new().decode(&mut buf).is_err());
+        let mut buf = BytesMut::from(&b"(12a\r\n"[..]);
+        assert!(RespDecoder::new().decode(&mut buf).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decoder_resumes_byte_by_byte() -> Result<()> {
+        let input = b"*3\r\n$3\r\nset\r\n*1\r\n:-42\r\n$5\r\nhello\r\n+OK\r\n";
+        let mut decoder = RespDecoder::new();
+        let mut buf = BytesMut::new();
+        let mut frames = Vec::new();
+        for b in input {
+            buf.extend_from_slice(&[*b]);
+            if let Some(frame) = decoder.decode(&mut buf)? {
+                frames.push(frame);
+            }
+        }
+
+        assert_eq!(
+            frames,
+            vec![
+                RespArray::new([
+                    b"set".into(),
+                    RespArray::new([(-42).into()]).into(),
+                    b"hello".into()
+                ])
+                .into(),
+                SimpleString::new("OK").into(),
+            ]
+        );
+        assert!(buf.is_empty());
+        assert!(decoder.is_idle());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decoder_keeps_progress() -> Result<()> {
+        let mut decoder = RespDecoder::new();
+        let mut buf = BytesMut::from(&b"*2\r\n$3\r\nget\r\n$5\r\nhel"[..]);
+        assert_eq!(decoder.decode(&mut buf)?, None);
+        // nothing is consumed until the frame is complete, but the first element is parsed
+        assert_eq!(buf.len(), 20);
+        assert!(!decoder.is_idle());
+        assert_eq!(decoder.pos, 13);
+
+        buf.extend_from_slice(b"lo\r\n");
+        assert_eq!(
+            decoder.decode(&mut buf)?,
+            Some(RespArray::new([b"get".into(), b"hello".into()]).into())
+        );
+        assert!(decoder.is_idle());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decoder_invalid() {
+        let mut decoder = RespDecoder::new();
+        let mut buf = BytesMut::from(&b"*2\r\n$3\r\nget\r\n?\r\n"[..]);
+        assert!(decoder.decode(&mut buf).is_err());
+        assert!(decoder.is_idle());
+
+        let mut buf = BytesMut::from(&b"$3\r\nabcd\r\n"[..]);
+        assert!(decoder.decode(&mut buf).is_err());
+
+        let mut buf = BytesMut::from(&b"=5\r\ntxt-a\r\n"[..]);
+        assert!(decoder.decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_decoder_limits() -> Result<()> {
+        let limits = RespLimits {
+            max_bulk_len: 4,
+            max_multibulk_len: 2,
+            max_nesting_depth: 2,
+            max_inline_len: 8,
+        };
+        let too_big = |input: &[u8], msg: &str| {
+            let mut buf = BytesMut::from(input);
+            let err = RespDecoder::with_limits(limits)
+                .decode(&mut buf)
+                .unwrap_err();
+            assert_eq!(err, RespError::LimitExceeded(msg.to_string()));
+        };
+        too_big(b"$5\r\nhello\r\n", "invalid bulk length");
+        too_big(b"!99\r\n", "invalid bulk length");
+        too_big(b"*3\r\n", "invalid multibulk length");
+        too_big(b"%3\r\n", "invalid multibulk length");
+        too_big(b"*1\r\n*1\r\n*0\r\n", "too many nested aggregates");
+        too_big(b"+123456789\r\n", "too big line");
+        // the limit applies before the CRLF arrives
+        too_big(b":1234567890", "too big line");
+
+        let mut buf = BytesMut::from(&b"*2\r\n*1\r\n$4\r\nabcd\r\n+12345678\r\n"[..]);
+        let frame = RespDecoder::with_limits(limits).decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            Some(
+                RespArray::new([
+                    RespArray::new([b"abcd".into()]).into(),
+                    SimpleString::new("12345678").into()
+                ])
+                .into()
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_decoder_huge_length_does_not_allocate() -> Result<()> {
+        let mut decoder = RespDecoder::new();
+        let mut buf = BytesMut::from(&b"*2147483647\r\n$3\r\nget\r\n"[..]);
+        assert_eq!(decoder.decode(&mut buf)?, None);
+        assert!(decoder.stack[0].items.capacity() <= MAX_PREALLOC);
+        Ok(())
+    }
+
+    #[test]
+    fn test_decoder_map_keeps_order() -> Result<()> {
+        let mut buf = BytesMut::from(&b"%3\r\n:2\r\n+b\r\n$1\r\na\r\n:1\r\n#f\r\n_\r\n"[..]);
+        let Some(RespFrame::Map(map)) = RespDecoder::new().decode(&mut buf)? else {
+            panic!("expect a map");
+        };
+        assert_eq!(
+            map.keys().cloned().collect::<Vec<_>>(),
+            vec![RespFrame::from(2), b"a".into(), false.into()]
+        );
+        assert_eq!(map["a"], 1.into());
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+use std::fmt::{self, Display, Write};
+
+use super::{double::format_double, RespArray, RespFrame, RespMap};
+
+/// print the frame the way redis-cli does, e.g. `1) "hello"` or `(integer) 5`
+impl Display for RespFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        format_frame(&mut out, self, "");
+        // every reply ends with a newline, the caller decides about the last one
+        f.write_str(out.strip_suffix('\n').unwrap_or(&out))
+    }
+}
+
+/// append the reply and its newline, `prefix` indents the lines after the first one
+fn format_frame(out: &mut String, frame: &RespFrame, prefix: &str) {
+    match frame {
+        RespFrame::SimpleString(s) => out.push_str(&String::from_utf8_lossy(s)),
+        RespFrame::Error(e) => {
+            out.push_str("(error) ");
+            out.push_str(e);
+        }
+        RespFrame::BulkError(e) => {
+            out.push_str("(error) ");
+            out.push_str(&String::from_utf8_lossy(&e.0));
+        }
+        RespFrame::Integer(n) => {
+            let _ = write!(out, "(integer) {}", n);
+        }
+        RespFrame::Double(d) => {
+            let _ = write!(out, "(double) {}", format_double(*d));
+        }
+        RespFrame::BigNumber(n) => {
+            let _ = write!(out, "(big number) {}", n.as_str());
+        }
+        RespFrame::Boolean(b) => {
+            let _ = write!(out, "({})", b);
+        }
+        RespFrame::BulkString(s) => match s.0.as_ref() {
+            Some(s) => push_quoted(out, s),
+            None => out.push_str("(nil)"),
+        },
+        RespFrame::Null(_) | RespFrame::Array(RespArray(None)) => out.push_str("(nil)"),
+        // verbatim strings are meant to be shown as is
+        RespFrame::VerbatimString(s) => out.push_str(&String::from_utf8_lossy(&s.data)),
+        // aggregates end with the newline of their last element
+        RespFrame::Array(RespArray(Some(items))) => {
+            return format_items(
+                out,
+                items.iter().map(|f| (f, None)),
+                items.len(),
+                ')',
+                prefix,
+            )
+        }
+        RespFrame::Set(s) => {
+            return format_items(out, s.0.iter().map(|f| (f, None)), s.0.len(), '~', prefix)
+        }
+        RespFrame::Push(p) => {
+            return format_items(out, p.0.iter().map(|f| (f, None)), p.0.len(), ')', prefix)
+        }
+        RespFrame::Map(map) => return format_map(out, map, '#', prefix),
+        RespFrame::Attribute(a) => {
+            format_map(out, &a.attrs, '|', prefix);
+            out.push_str(prefix);
+            return format_frame(out, &a.frame, prefix);
+        }
+    }
+    out.push('\n');
+}
+
+fn format_map(out: &mut String, map: &RespMap, sep: char, prefix: &str) {
+    // redis-cli numbers the entries, not their keys and values
+    let entries = map.iter().map(|(k, v)| (k, Some(v)));
+    format_items(out, entries, map.len(), sep, prefix)
+}
+
+/// numbered elements, nested aggregates are indented under their number
+fn format_items<'a>(
+    out: &mut String,
+    items: impl Iterator<Item = (&'a RespFrame, Option<&'a RespFrame>)>,
+    len: usize,
+    sep: char,
+    prefix: &str,
+) {
+    if len == 0 {
+        out.push_str(match sep {
+            '~' => "(empty set)\n",
+            '#' => "(empty hash)\n",
+            _ => "(empty array)\n",
+        });
+        return;
+    }
+    let width = len.to_string().len();
+    let nested = format!("{}{}", prefix, " ".repeat(width + 2));
+    for (i, (item, value)) in items.enumerate() {
+        // the first line follows the number printed by the parent
+        let _ = write!(
+            out,
+            "{}{:>width$}{} ",
+            if i == 0 { "" } else { prefix },
+            i + 1,
+            sep,
+        );
+        format_frame(out, item, &nested);
+        if let Some(value) = value {
+            out.pop();
+            out.push_str(" => ");
+            format_frame(out, value, &nested);
+        }
+    }
+}
+
+/// quote a string like redis-cli, escaping what is not printable
+fn push_quoted(out: &mut String, s: &[u8]) {
+    out.push('"');
+    for &c in s {
+        match c {
+            b'\\' => out.push_str("\\\\"),
+            b'"' => out.push_str("\\\""),
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            0x07 => out.push_str("\\a"),
+            0x08 => out.push_str("\\b"),
+            c if c.is_ascii_graphic() || c == b' ' => out.push(c as char),
+            c => {
+                let _ = write!(out, "\\x{:02x}", c);
+            }
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BulkString, RespAttribute, RespBigNumber, RespNull, RespSet, SimpleError, SimpleString,
+        VerbatimString,
+    };
+    use anyhow::Result;
+
+    #[test]
+    fn test_display_scalars() -> Result<()> {
+        assert_eq!(RespFrame::from(SimpleString::new("OK")).to_string(), "OK");
+        assert_eq!(
+            RespFrame::from(SimpleError::new("ERR boom")).to_string(),
+            "(error) ERR boom"
+        );
+        assert_eq!(RespFrame::from(5).to_string(), "(integer) 5");
+        assert_eq!(RespFrame::from(1.5).to_string(), "(double) 1.5");
+        assert_eq!(RespFrame::from(true).to_string(), "(true)");
+        assert_eq!(RespFrame::from(RespNull).to_string(), "(nil)");
+        assert_eq!(RespFrame::from(BulkString::new_null()).to_string(), "(nil)");
+        assert_eq!(
+            RespFrame::from(RespBigNumber::new("123")?).to_string(),
+            "(big number) 123"
+        );
+        assert_eq!(
+            RespFrame::from(b"a \"b\"\r\n\xff").to_string(),
+            r#""a \"b\"\r\n\xff""#
+        );
+        assert_eq!(
+            RespFrame::from(VerbatimString::txt("# Server\r\nx:1")).to_string(),
+            "# Server\r\nx:1"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_display_aggregates() {
+        let frame: RespFrame = RespArray::new([
+            b"hello".into(),
+            RespArray::new([1.into(), RespArray::new([]).into()]).into(),
+            RespSet::new([b"a".into()]).into(),
+        ])
+        .into();
+        assert_eq!(
+            frame.to_string(),
+            "1) \"hello\"\n\
+             2) 1) (integer) 1\n   \
+                2) (empty array)\n\
+             3) 1~ \"a\""
+        );
+
+        let items = (1..=10).map(RespFrame::from).collect::<Vec<_>>();
+        let lines = RespFrame::from(RespArray::new(items)).to_string();
+        assert!(lines.starts_with(" 1) (integer) 1\n 2) (integer) 2\n"));
+        assert!(lines.ends_with("\n10) (integer) 10"));
+
+        let mut map = RespMap::new();
+        map.insert(b"name", b"redis");
+        map.insert(b"modules", RespArray::new([b"a".into(), b"b".into()]));
+        assert_eq!(
+            RespFrame::from(map).to_string(),
+            "1# \"name\" => \"redis\"\n\
+             2# \"modules\" => 1) \"a\"\n   \
+                              2) \"b\""
+        );
+        assert_eq!(RespFrame::from(RespMap::new()).to_string(), "(empty hash)");
+
+        let mut map = RespMap::new();
+        for i in 1..=5 {
+            map.push(i, RespArray::new([b"a".into(), b"b".into()]));
+        }
+        let lines = RespFrame::from(map).to_string();
+        assert!(lines.starts_with("1# (integer) 1 => 1) \"a\"\n   2) \"b\"\n2# "));
+        assert!(lines.ends_with("\n5# (integer) 5 => 1) \"a\"\n   2) \"b\""));
+    }
+
+    #[test]
+    fn test_display_attribute() {
+        let mut attrs = RespMap::new();
+        attrs.insert(b"ttl", 3);
+        let frame: RespFrame = RespAttribute::new(attrs, b"v").into();
+        assert_eq!(frame.to_string(), "1| \"ttl\" => (integer) 3\n\"v\"");
+    }
+}
@@ -1,41 +1,53 @@
-use crate::protocol::{RespDecode, RespEncode, RespError};
-use bytes::BytesMut;
+use crate::protocol::{RespDecode, RespEncode, RespError, RespFrame};
+use bytes::{BufMut, BytesMut};
+use std::fmt::Write;
 
 /// ,[<+|->]<integral>[.<fractional>][<E|e>[sign]<exponent>]\r\n
-
+/// or `inf`, `-inf` and `nan`
 impl RespDecode for f64 {
     const PREFIX: &'static str = ",";
     const TYPE: &'static str = "double";
 
     /// decode f64 from RESP
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        // CRLF start
-        let end = super::extract_simple_frame_data(buf, Self::PREFIX)?;
-        // data
-        let data = buf.split_to(end + super::CRLF_LEN);
-        // data body
-        let s = String::from_utf8_lossy(&data[Self::PREFIX.len()..end]);
-        Ok(s.parse()?)
+        super::decode_frame_as(buf, Self::PREFIX, |frame| match frame {
+            RespFrame::Double(v) => Some(v),
+            _ => None,
+        })
     }
+}
 
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
-        let end = super::extract_simple_frame_data(buf, Self::PREFIX)?;
-        Ok(end + super::CRLF_LEN)
+impl RespEncode for f64 {
+    /// the shortest decimal that reads back as the same value, without exponent
+    fn encode_to<B: BufMut>(&self, buf: &mut B) {
+        buf.put_u8(b',');
+        match special_double(*self) {
+            Some(s) => buf.put_slice(s.as_bytes()),
+            // writing to a buffer can't fail
+            None => {
+                let _ = write!(super::BufWriter(buf), "{}", self);
+            }
+        }
+        buf.put_slice(super::CRLF);
     }
 }
 
-impl RespEncode for f64 {
-    fn encode(self) -> Vec<u8> {
-        let mut buf = Vec::new();
-        let ret = if self.abs() > 1e+8 || self.abs() < 1e-8 {
-            format!(",{:+e}\r\n", self)
-        } else {
-            let sign = if self < 0.0 { "" } else { "+" };
-            format!(",{}{}\r\n", sign, self)
-        };
+/// the textual form of a double, shared by RESP3 doubles and RESP2 bulk strings
+pub(crate) fn format_double(d: f64) -> String {
+    match special_double(d) {
+        Some(s) => s.to_string(),
+        None => d.to_string(),
+    }
+}
 
-        buf.extend_from_slice(&ret.into_bytes());
-        buf
+/// the names the spec gives to values without a decimal form
+fn special_double(d: f64) -> Option<&'static str> {
+    if d.is_nan() {
+        Some("nan")
+    } else if d.is_infinite() {
+        Some(if d > 0.0 { "inf" } else { "-inf" })
+    } else {
+        None
     }
 }
 
@@ -48,7 +60,7 @@ mod tests {
     fn test_f64() {
         let s = 3.15;
         let encoded = s.encode();
-        assert_eq!(encoded, b",+3.15\r\n");
+        assert_eq!(encoded, b",3.15\r\n");
         let mut data = BytesMut::new();
         data.extend_from_slice(b",+3.15\r\n");
         let decoded = f64::decode(&mut data).unwrap();
@@ -69,7 +81,9 @@ mod tests {
     #[test]
     fn test_f64_large() {
         let frame: RespFrame = 1.23456e+8.into();
-        assert_eq!(frame.encode(), b",+1.23456e8\r\n");
+        assert_eq!(frame.encode(), b",123456000\r\n");
+        let mut data = BytesMut::from(&b",1.23456e8\r\n"[..]);
+        assert_eq!(f64::decode(&mut data).unwrap(), 1.23456e+8);
     }
 
     #[test]
@@ -77,10 +91,23 @@ mod tests {
         let s = 0.00000001;
         let encoded = s.encode();
         let result = String::from_utf8(encoded).unwrap();
-        assert_eq!(result.as_bytes(), b",+0.00000001\r\n");
+        assert_eq!(result.as_bytes(), b",0.00000001\r\n");
         let mut data = BytesMut::new();
         data.extend_from_slice(b",+0.00000001\r\n");
         let decoded = f64::decode(&mut data).unwrap();
         assert_eq!(decoded, s);
     }
+
+    #[test]
+    fn test_f64_special() {
+        assert_eq!(f64::INFINITY.encode(), b",inf\r\n");
+        assert_eq!(f64::NEG_INFINITY.encode(), b",-inf\r\n");
+        assert_eq!(f64::NAN.encode(), b",nan\r\n");
+
+        let mut data = BytesMut::from(&b",inf\r\n,-inf\r\n,nan\r\n"[..]);
+        assert_eq!(f64::decode(&mut data).unwrap(), f64::INFINITY);
+        assert_eq!(f64::decode(&mut data).unwrap(), f64::NEG_INFINITY);
+        assert!(f64::decode(&mut data).unwrap().is_nan());
+        assert!(data.is_empty());
+    }
 }
@@ -0,0 +1,220 @@
+use bytes::BytesMut;
+
+use super::{BulkString, RespArray, RespError, RespFrame};
+
+/// decode an inline command: arguments separated by spaces on a single line, terminated by
+/// "\n" or "\r\n". Arguments may be quoted, double quotes support escape sequences.
+/// An empty line decodes into an empty array. Lines longer than `max_len` are rejected.
+pub(crate) fn decode_inline(buf: &mut BytesMut, max_len: usize) -> Result<RespArray, RespError> {
+    let too_big = || RespError::LimitExceeded("too big inline request".to_string());
+    let Some(end) = buf.iter().position(|b| *b == b'\n') else {
+        if buf.len() > max_len {
+            return Err(too_big());
+        }
+        return Err(RespError::NotComplete);
+    };
+    if end > max_len + 1 {
+        return Err(too_big());
+    }
+
+    let line = buf.split_to(end + 1);
+    let line = match line[..end].strip_suffix(b"\r") {
+        Some(line) => line,
+        None => &line[..end],
+    };
+
+    let args = split_args(line)?
+        .into_iter()
+        .map(|arg| BulkString::new(arg).into())
+        .collect::<Vec<RespFrame>>();
+    Ok(RespArray::new(args))
+}
+
+/// split a line into arguments the way redis-cli and the server do
+fn split_args(line: &[u8]) -> Result<Vec<Vec<u8>>, RespError> {
+    let unbalanced = || RespError::InvalidInline("unbalanced quotes in request".to_string());
+    let ends_arg = |i: usize| line.get(i).is_none_or(u8::is_ascii_whitespace);
+    let mut args = Vec::new();
+    let mut i = 0;
+
+    loop {
+        while i < line.len() && line[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if i == line.len() {
+            return Ok(args);
+        }
+
+        let mut arg = Vec::new();
+        let mut in_double = false;
+        let mut in_single = false;
+        loop {
+            let Some(&c) = line.get(i) else {
+                if in_double || in_single {
+                    return Err(unbalanced());
+                }
+                break;
+            };
+
+            if in_double {
+                match (c, line.get(i + 1)) {
+                    (b'\\', Some(b'x'))
+                        if line.get(i + 2).is_some_and(u8::is_ascii_hexdigit)
+                            && line.get(i + 3).is_some_and(u8::is_ascii_hexdigit) =>
+                    {
+                        arg.push(hex_value(line[i + 2]) * 16 + hex_value(line[i + 3]));
+                        i += 3;
+                    }
+                    (b'\\', Some(&escaped)) => {
+                        arg.push(match escaped {
+                            b'n' => b'\n',
+                            b'r' => b'\r',
+                            b't' => b'\t',
+                            b'b' => 0x08,
+                            b'a' => 0x07,
+                            c => c,
+                        });
+                        i += 1;
+                    }
+                    (b'"', _) => {
+                        // the closing quote must end the argument
+                        if !ends_arg(i + 1) {
+                            return Err(unbalanced());
+                        }
+                        i += 1;
+                        break;
+                    }
+                    (c, _) => arg.push(c),
+                }
+            } else if in_single {
+                match (c, line.get(i + 1)) {
+                    (b'\\', Some(b'\'')) => {
+                        arg.push(b'\'');
+                        i += 1;
+                    }
+                    (b'\'', _) => {
+                        if !ends_arg(i + 1) {
+                            return Err(unbalanced());
+                        }
+                        i += 1;
+                        break;
+                    }
+                    (c, _) => arg.push(c),
+                }
+            } else {
+                match c {
+                    c if c.is_ascii_whitespace() => break,
+                    b'"' => in_double = true,
+                    b'\'' => in_single = true,
+                    c => arg.push(c),
+                }
+            }
+            i += 1;
+        }
+        args.push(arg);
+    }
+}
+
+fn hex_value(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        _ => c - b'A' + 10,
+    }
+}
+
+/// true if the byte is the type of a RESP frame
+pub(crate) fn is_resp_prefix(b: u8) -> bool {
+    matches!(
+        b,
+        b'+' | b'-'
+            | b':'
+            | b'$'
+            | b'*'
+            | b'_'
+            | b'#'
+            | b','
+            | b'%'
+            | b'~'
+            | b'>'
+            | b'('
+            | b'='
+            | b'!'
+            | b'|'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    const MAX_LEN: usize = 64 * 1024;
+
+    #[test]
+    fn test_decode_inline() -> Result<()> {
+        let mut buf = BytesMut::from("SET a \"hello world\"\r\nPING\n");
+        let frame = decode_inline(&mut buf, MAX_LEN)?;
+        assert_eq!(
+            frame,
+            RespArray::new([b"SET".into(), b"a".into(), b"hello world".into()])
+        );
+
+        let frame = decode_inline(&mut buf, MAX_LEN)?;
+        assert_eq!(frame, RespArray::new([b"PING".into()]));
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_inline_not_complete() {
+        let mut buf = BytesMut::from("GET a");
+        let ret = decode_inline(&mut buf, MAX_LEN);
+        assert_eq!(ret.unwrap_err(), RespError::NotComplete);
+        assert_eq!(&buf[..], b"GET a");
+    }
+
+    #[test]
+    fn test_decode_inline_too_big() {
+        let mut buf = BytesMut::from("GET abcdef");
+        assert_eq!(
+            decode_inline(&mut buf, 8).unwrap_err(),
+            RespError::LimitExceeded("too big inline request".to_string())
+        );
+
+        let mut buf = BytesMut::from("GET abcdef\r\n");
+        assert!(decode_inline(&mut buf, 8).is_err());
+        let mut buf = BytesMut::from("GET abc\r\n");
+        assert!(decode_inline(&mut buf, 8).is_ok());
+    }
+
+    #[test]
+    fn test_decode_inline_quotes() -> Result<()> {
+        let mut buf = BytesMut::from("set 'it\\'s' \"a\\x41\\n\"  \r\n");
+        let frame = decode_inline(&mut buf, MAX_LEN)?;
+        assert_eq!(
+            frame,
+            RespArray::new([b"set".into(), b"it's".into(), b"aA\n".into()])
+        );
+
+        let mut buf = BytesMut::from("\r\n");
+        assert_eq!(decode_inline(&mut buf, MAX_LEN)?, RespArray::new([]));
+
+        let mut buf = BytesMut::from("set \"a\r\n");
+        assert_eq!(
+            decode_inline(&mut buf, MAX_LEN).unwrap_err(),
+            RespError::InvalidInline("unbalanced quotes in request".to_string())
+        );
+
+        let mut buf = BytesMut::from("set \"a\"b\r\n");
+        assert!(decode_inline(&mut buf, MAX_LEN).is_err());
+
+        // an invalid hex escape is kept as is
+        let mut buf = BytesMut::from("echo \"\\xZZ\"\r\n");
+        assert_eq!(
+            decode_inline(&mut buf, MAX_LEN)?,
+            RespArray::new([b"echo".into(), b"xZZ".into()])
+        );
+        Ok(())
+    }
+}
@@ -1,30 +1,23 @@
-use crate::protocol::{RespDecode, RespEncode, RespError};
-use bytes::BytesMut;
+use crate::protocol::{RespDecode, RespEncode, RespError, RespFrame};
+use bytes::{BufMut, BytesMut};
 
 /// :[<+|->]<value>\r\n
-
 impl RespDecode for i64 {
     const PREFIX: &'static str = ":";
     const TYPE: &'static str = "integer";
 
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        Self::check_format(buf)?;
-        let end = super::find_crlf(buf, 1).ok_or(RespError::NotComplete)?;
-        let data = buf.split_to(end + super::CRLF_LEN);
-        let s = String::from_utf8_lossy(&data[Self::PREFIX.len()..end]);
-        Ok(s.parse()?)
-    }
-
-    fn expect_length(_buf: &[u8]) -> Result<usize, RespError> {
-        todo!()
+        super::decode_frame_as(buf, Self::PREFIX, |frame| match frame {
+            RespFrame::Integer(v) => Some(v),
+            _ => None,
+        })
     }
 }
 
 impl RespEncode for i64 {
-    fn encode(self) -> Vec<u8> {
-        let sign = if self < 0 { "" } else { "+" };
-        let key = format!(":{}{}\r\n", sign, self);
-        key.into_bytes()
+    /// the sign is only written for negative numbers, like redis does
+    fn encode_to<B: BufMut>(&self, buf: &mut B) {
+        super::put_prefixed_int(buf, b':', *self);
     }
 }
 
@@ -45,13 +38,14 @@ mod tests {
 
         let frame = i64::decode(&mut buf)?;
         assert_eq!(frame, -123);
+        assert!(buf.is_empty());
         Ok(())
     }
 
     #[test]
     fn test_integer_encode() {
-        let i = 123;
-        let data = i.encode();
-        assert_eq!(data, b":+123\r\n");
+        assert_eq!(123.encode(), b":123\r\n");
+        assert_eq!((-123).encode(), b":-123\r\n");
+        assert_eq!(i64::MIN.encode(), b":-9223372036854775808\r\n");
     }
 }
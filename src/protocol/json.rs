@@ -0,0 +1,280 @@
+use serde_json::{json, Map, Value};
+
+use super::{
+    BulkError, BulkString, RespArray, RespAttribute, RespBigNumber, RespError, RespFrame, RespMap,
+    RespNull, RespPush, RespSet, SimpleError, SimpleString, VerbatimString,
+};
+
+// Frames are converted without loss, doubles too as serde_json is built with `float_roundtrip`.
+// The common types map to plain JSON: bulk strings to strings, integers to numbers, arrays to
+// arrays, booleans to booleans and null to null. Every other type is an object with a single key
+// naming it, e.g. `{"simple": "OK"}`, `{"map": [[key, value], ...]}` or `{"bulk": null}`. Bytes
+// that are not UTF-8 are written as `{"hex": "ff00"}`.
+
+impl From<&RespFrame> for Value {
+    fn from(frame: &RespFrame) -> Self {
+        match frame {
+            RespFrame::BulkString(BulkString(Some(s))) => bytes_value(s),
+            RespFrame::BulkString(BulkString(None)) => json!({ "bulk": null }),
+            RespFrame::Integer(n) => (*n).into(),
+            RespFrame::Array(RespArray(Some(items))) => items.iter().map(Value::from).collect(),
+            RespFrame::Array(RespArray(None)) => json!({ "array": null }),
+            RespFrame::Boolean(b) => (*b).into(),
+            RespFrame::Null(_) => Value::Null,
+            RespFrame::SimpleString(s) => json!({ "simple": bytes_value(s) }),
+            RespFrame::Error(e) => json!({ "error": e.0 }),
+            RespFrame::BulkError(e) => json!({ "bulk_error": bytes_value(&e.0) }),
+            // JSON has no inf or nan, and 1.0 would read back as an integer
+            RespFrame::Double(d) => match serde_json::Number::from_f64(*d) {
+                Some(n) => json!({ "double": n }),
+                None => json!({ "double": super::double::format_double(*d) }),
+            },
+            RespFrame::BigNumber(n) => json!({ "big_number": n.0 }),
+            RespFrame::VerbatimString(s) => json!({
+                "verbatim": [String::from_utf8_lossy(&s.format), bytes_value(&s.data)]
+            }),
+            RespFrame::Map(map) => json!({ "map": entries_value(map) }),
+            RespFrame::Set(s) => json!({ "set": s.0.iter().map(Value::from).collect::<Vec<_>>() }),
+            RespFrame::Push(p) => {
+                json!({ "push": p.0.iter().map(Value::from).collect::<Vec<_>>() })
+            }
+            RespFrame::Attribute(a) => json!({
+                "attribute": { "attrs": entries_value(&a.attrs), "frame": Value::from(&*a.frame) }
+            }),
+        }
+    }
+}
+
+impl From<RespFrame> for Value {
+    fn from(frame: RespFrame) -> Self {
+        Value::from(&frame)
+    }
+}
+
+impl TryFrom<Value> for RespFrame {
+    type Error = RespError;
+
+    fn try_from(value: Value) -> Result<Self, RespError> {
+        match value {
+            Value::Null => Ok(RespNull.into()),
+            Value::Bool(b) => Ok(b.into()),
+            Value::Number(n) => n
+                .as_i64()
+                .map(RespFrame::from)
+                .ok_or_else(|| invalid(format!("{} is not an integer, use {{\"double\": ..}}", n))),
+            Value::String(s) => Ok(BulkString::from(s).into()),
+            Value::Array(items) => Ok(RespArray::new(frames(items)?).into()),
+            Value::Object(obj) => tagged_frame(obj),
+        }
+    }
+}
+
+fn tagged_frame(obj: Map<String, Value>) -> Result<RespFrame, RespError> {
+    let mut entries = obj.into_iter();
+    let (Some((tag, value)), None) = (entries.next(), entries.next()) else {
+        return Err(invalid("a frame object must have a single key".to_string()));
+    };
+    let frame = match (tag.as_str(), value) {
+        ("hex", Value::String(s)) => BulkString::from(from_hex(&s)?).into(),
+        ("bulk", Value::Null) => BulkString::new_null().into(),
+        ("array", Value::Null) => RespArray::new_null().into(),
+        ("simple", v) => SimpleString::new(value_bytes(v)?).into(),
+        ("error", Value::String(s)) => SimpleError::new(s).into(),
+        ("bulk_error", v) => BulkError::new(value_bytes(v)?).into(),
+        ("double", Value::Number(n)) => n
+            .as_f64()
+            .ok_or_else(|| invalid(format!("invalid double {}", n)))?
+            .into(),
+        ("double", Value::String(s)) => match s.as_str() {
+            "inf" | "-inf" | "nan" => s.parse::<f64>()?.into(),
+            _ => return Err(invalid(format!("invalid double {:?}", s))),
+        },
+        ("big_number", Value::String(s)) => RespBigNumber::new(s)?.into(),
+        ("verbatim", Value::Array(parts)) => {
+            let [Value::String(format), data] = <[Value; 2]>::try_from(parts)
+                .map_err(|_| invalid("verbatim must be [format, data]".to_string()))?
+            else {
+                return Err(invalid("verbatim must be [format, data]".to_string()));
+            };
+            let format = <[u8; 3]>::try_from(format.as_bytes())
+                .map_err(|_| invalid(format!("invalid verbatim format {:?}", format)))?;
+            VerbatimString::new(format, value_bytes(data)?).into()
+        }
+        ("map", Value::Array(entries)) => entries_map(entries)?.into(),
+        ("set", Value::Array(items)) => RespSet::new(frames(items)?).into(),
+        ("push", Value::Array(items)) => RespPush::new(frames(items)?).into(),
+        ("attribute", Value::Object(mut obj)) => {
+            let (Some(Value::Array(attrs)), Some(frame)) =
+                (obj.remove("attrs"), obj.remove("frame"))
+            else {
+                return Err(invalid("attribute must have attrs and frame".to_string()));
+            };
+            RespAttribute::new(entries_map(attrs)?, RespFrame::try_from(frame)?).into()
+        }
+        (tag, value) => return Err(invalid(format!("invalid frame {{{:?}: {}}}", tag, value))),
+    };
+    Ok(frame)
+}
+
+fn invalid(msg: String) -> RespError {
+    RespError::InvalidFrame(msg)
+}
+
+fn frames(items: Vec<Value>) -> Result<Vec<RespFrame>, RespError> {
+    items.into_iter().map(RespFrame::try_from).collect()
+}
+
+fn entries_value(map: &RespMap) -> Value {
+    map.iter()
+        .map(|(k, v)| json!([Value::from(k), Value::from(v)]))
+        .collect()
+}
+
+fn entries_map(entries: Vec<Value>) -> Result<RespMap, RespError> {
+    let mut map = RespMap::with_capacity(entries.len());
+    for entry in entries {
+        let Ok([k, v]) = <[Value; 2]>::try_from(match entry {
+            Value::Array(entry) => entry,
+            _ => Vec::new(),
+        }) else {
+            return Err(invalid("map entries must be [key, value]".to_string()));
+        };
+        // the JSON came from a map, keys are unique already
+        map.push(RespFrame::try_from(k)?, RespFrame::try_from(v)?);
+    }
+    Ok(map)
+}
+
+/// a string if the bytes are UTF-8, `{"hex": ..}` otherwise
+fn bytes_value(bytes: &[u8]) -> Value {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s.into(),
+        Err(_) => {
+            let hex = bytes
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>();
+            json!({ "hex": hex })
+        }
+    }
+}
+
+fn value_bytes(value: Value) -> Result<Vec<u8>, RespError> {
+    match value {
+        Value::String(s) => Ok(s.into_bytes()),
+        Value::Object(mut obj) if obj.len() == 1 => match obj.remove("hex") {
+            Some(Value::String(s)) => from_hex(&s),
+            _ => Err(invalid("expected a string or {\"hex\": ..}".to_string())),
+        },
+        _ => Err(invalid("expected a string or {\"hex\": ..}".to_string())),
+    }
+}
+
+fn from_hex(s: &str) -> Result<Vec<u8>, RespError> {
+    if !s.len().is_multiple_of(2) {
+        return Err(invalid(format!("invalid hex {:?}", s)));
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            s.get(i..i + 2)
+                .and_then(|b| u8::from_str_radix(b, 16).ok())
+                .ok_or_else(|| invalid(format!("invalid hex {:?}", s)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    fn frames() -> Result<Vec<RespFrame>> {
+        let mut map = RespMap::new();
+        map.insert(b"name", b"redis");
+        map.insert(1, RespSet::new([SimpleString::new("a").into()]));
+        map.insert(RespArray::new([1.into()]), RespNull);
+        Ok(vec![
+            b"hello".into(),
+            b"\xff\x00bin".into(),
+            BulkString::new_null().into(),
+            RespArray::new_null().into(),
+            SimpleString::new("OK").into(),
+            SimpleError::new("ERR boom").into(),
+            BulkError::new("ERR a\r\nb").into(),
+            42.into(),
+            1.0.into(),
+            f64::NEG_INFINITY.into(),
+            true.into(),
+            RespNull.into(),
+            RespBigNumber::new("-123456789012345678901234567890")?.into(),
+            VerbatimString::txt("# Server").into(),
+            RespPush::new([b"message".into(), b"hi".into()]).into(),
+            RespArray::new([b"a".into(), 2.into(), RespArray::new([]).into()]).into(),
+            map.clone().into(),
+            RespAttribute::new(map, b"v").into(),
+        ])
+    }
+
+    #[test]
+    fn test_json_roundtrip() -> Result<()> {
+        for frame in frames()? {
+            let value = Value::from(&frame);
+            // through the text form too, as a fixture would be stored
+            let text = serde_json::to_string(&value)?;
+            let parsed: Value = serde_json::from_str(&text)?;
+            assert_eq!(RespFrame::try_from(parsed)?, frame, "{}", text);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_double_roundtrip() -> Result<()> {
+        // 17 significant digits, the default float parser of serde_json reads some of them back
+        // one ulp off
+        for d in [
+            1.1362275116276523e-8,
+            2.2201838057111728e-13,
+            0.30000000000000004,
+            1.7976931348623157e308,
+        ] {
+            let frame = RespFrame::Double(d);
+            let text = serde_json::to_string(&Value::from(&frame))?;
+            let parsed: Value = serde_json::from_str(&text)?;
+            assert_eq!(RespFrame::try_from(parsed)?, frame, "{}", text);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_shape() {
+        let frame: RespFrame = RespArray::new([
+            b"hello".into(),
+            5.into(),
+            RespNull.into(),
+            SimpleString::new("OK").into(),
+            b"\xff".into(),
+            1.5.into(),
+        ])
+        .into();
+        assert_eq!(
+            Value::from(frame),
+            json!(["hello", 5, null, {"simple": "OK"}, {"hex": "ff"}, {"double": 1.5}])
+        );
+    }
+
+    #[test]
+    fn test_json_invalid() {
+        for value in [
+            json!(1.5),
+            json!({"double": "x"}),
+            json!({"hex": "f"}),
+            json!({"simple": "a", "error": "b"}),
+            json!({"map": [[1]]}),
+            json!({"verbatim": ["text", "a"]}),
+            json!({"unknown": 1}),
+        ] {
+            assert!(RespFrame::try_from(value.clone()).is_err(), "{}", value);
+        }
+    }
+}
@@ -1,29 +1,56 @@
-use bytes::{Buf, BytesMut};
+use bytes::{BufMut, BytesMut};
 
-use crate::protocol::simple_string::SimpleString;
-use std::{
-    collections::BTreeMap,
-    ops::{Deref, DerefMut},
-};
+use std::ops::{Deref, Index};
 
-use super::{
-    calc_total_length, parse_length, RespDecode, RespEncode, RespError, RespFrame, CRLF_LEN,
-};
-
-/// %<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>"
+use super::{BulkString, RespDecode, RespEncode, RespError, RespFrame};
 
+/// %<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>
+/// keys can be any frame, entries keep the order they were inserted in
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
-pub struct RespMap(pub(crate) BTreeMap<String, RespFrame>);
+pub struct RespMap(pub(crate) Vec<(RespFrame, RespFrame)>);
+
+/// something a map key can be looked up with
+pub trait MapKey {
+    fn matches(&self, key: &RespFrame) -> bool;
+}
+
+impl MapKey for RespFrame {
+    fn matches(&self, key: &RespFrame) -> bool {
+        self == key
+    }
+}
+
+/// matches a simple or bulk string with these bytes
+impl MapKey for [u8] {
+    fn matches(&self, key: &RespFrame) -> bool {
+        match key {
+            RespFrame::SimpleString(s) => s.as_ref() == self,
+            RespFrame::BulkString(BulkString(Some(s))) => s.as_ref() == self,
+            _ => false,
+        }
+    }
+}
+
+impl MapKey for str {
+    fn matches(&self, key: &RespFrame) -> bool {
+        self.as_bytes().matches(key)
+    }
+}
 
 impl RespEncode for RespMap {
-    fn encode(self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(4096);
-        buf.extend_from_slice(&format!("%{}\r\n", self.len()).into_bytes());
-        for (key, value) in self.0 {
-            buf.extend_from_slice(&SimpleString::new(key).encode());
-            buf.extend_from_slice(&value.encode());
+    fn encode_to<B: BufMut>(&self, buf: &mut B) {
+        super::put_prefixed_int(buf, b'%', self.len());
+        self.encode_entries_to(buf);
+    }
+}
+
+impl RespMap {
+    /// write the keys and values without the header, attributes share this layout
+    pub(crate) fn encode_entries_to<B: BufMut>(&self, buf: &mut B) {
+        for (key, value) in &self.0 {
+            key.encode_to(buf);
+            value.encode_to(buf);
         }
-        buf
     }
 }
 
@@ -32,34 +59,76 @@ impl RespDecode for RespMap {
     const TYPE: &'static str = "map";
 
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?;
-        let total_len = calc_total_length(buf, end, len, Self::PREFIX)?;
+        super::decode_frame_as(buf, Self::PREFIX, |frame| match frame {
+            RespFrame::Map(v) => Some(v),
+            _ => None,
+        })
+    }
+}
 
-        if buf.len() < total_len {
-            return Err(RespError::NotComplete);
-        }
+impl RespMap {
+    pub fn new() -> Self {
+        RespMap(Vec::new())
+    }
 
-        buf.advance(end + CRLF_LEN);
+    pub fn with_capacity(capacity: usize) -> Self {
+        RespMap(Vec::with_capacity(capacity))
+    }
 
-        let mut frames = RespMap::new();
-        for _ in 0..len {
-            let key = SimpleString::decode(buf)?;
-            let value = RespFrame::decode(buf)?;
-            frames.insert(key.0, value);
+    /// set the value of `key`, an existing key keeps its position and its old value is returned
+    pub fn insert(
+        &mut self,
+        key: impl Into<RespFrame>,
+        value: impl Into<RespFrame>,
+    ) -> Option<RespFrame> {
+        let key = key.into();
+        let value = value.into();
+        match self.0.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => Some(std::mem::replace(v, value)),
+            None => {
+                self.0.push((key, value));
+                None
+            }
         }
+    }
 
-        Ok(frames)
+    /// append an entry without looking for an existing key, for callers whose keys are
+    /// known to be unique, like the entries of a hash
+    pub fn push(&mut self, key: impl Into<RespFrame>, value: impl Into<RespFrame>) {
+        self.0.push((key.into(), value.into()));
     }
 
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?;
-        calc_total_length(buf, end, len, Self::PREFIX)
+    /// the value of `key`, string keys can be looked up with a `&str` or `&[u8]` whether they
+    /// are simple or bulk strings
+    pub fn get<K>(&self, key: &K) -> Option<&RespFrame>
+    where
+        K: MapKey + ?Sized,
+    {
+        self.0.iter().find(|(k, _)| key.matches(k)).map(|(_, v)| v)
     }
-}
 
-impl RespMap {
-    pub fn new() -> Self {
-        RespMap(BTreeMap::new())
+    pub fn contains_key<K>(&self, key: &K) -> bool
+    where
+        K: MapKey + ?Sized,
+    {
+        self.get(key).is_some()
+    }
+
+    /// remove `key`, the following entries keep their order
+    pub fn remove<K>(&mut self, key: &K) -> Option<RespFrame>
+    where
+        K: MapKey + ?Sized,
+    {
+        let pos = self.0.iter().position(|(k, _)| key.matches(k))?;
+        Some(self.0.remove(pos).1)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &RespFrame> {
+        self.0.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &RespFrame> {
+        self.0.iter().map(|(_, v)| v)
     }
 }
 
@@ -70,16 +139,53 @@ impl Default for RespMap {
 }
 
 impl Deref for RespMap {
-    type Target = BTreeMap<String, RespFrame>;
+    type Target = [(RespFrame, RespFrame)];
 
     fn deref(&self) -> &Self::Target {
         &self.0
     }
 }
 
-impl DerefMut for RespMap {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+impl Index<&str> for RespMap {
+    type Output = RespFrame;
+
+    fn index(&self, key: &str) -> &Self::Output {
+        self.get(key)
+            .unwrap_or_else(|| panic!("key {:?} not found in map", key))
+    }
+}
+
+impl<K: Into<RespFrame>, V: Into<RespFrame>> FromIterator<(K, V)> for RespMap {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        let mut map = RespMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Into<RespFrame>, V: Into<RespFrame>> Extend<(K, V)> for RespMap {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl IntoIterator for RespMap {
+    type Item = (RespFrame, RespFrame);
+    type IntoIter = std::vec::IntoIter<(RespFrame, RespFrame)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a RespMap {
+    type Item = &'a (RespFrame, RespFrame);
+    type IntoIter = std::slice::Iter<'a, (RespFrame, RespFrame)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
     }
 }
 
@@ -87,22 +193,19 @@ impl DerefMut for RespMap {
 mod tests {
     use super::*;
     use crate::protocol::bulk_strings::BulkString;
-    use crate::protocol::RespFrame;
+    use crate::protocol::{RespArray, RespFrame, RespNull};
     use anyhow::Result;
 
     #[test]
     fn test_map_encode() {
         let mut map = RespMap::new();
-        map.insert(
-            "hello".to_string(),
-            BulkString::new("world".to_string()).into(),
-        );
-        map.insert("foo".to_string(), (-123456.789).into());
+        map.insert("hello", BulkString::new("world".to_string()));
+        map.insert("foo", -123456.789);
 
         let frame: RespFrame = map.into();
         assert_eq!(
             &frame.encode(),
-            b"%2\r\n+foo\r\n,-123456.789\r\n+hello\r\n$5\r\nworld\r\n"
+            b"%2\r\n+hello\r\n$5\r\nworld\r\n+foo\r\n,-123456.789\r\n"
         );
     }
 
@@ -113,13 +216,47 @@ mod tests {
 
         let frame = RespMap::decode(&mut buf)?;
         let mut map = RespMap::new();
-        map.insert(
-            "hello".to_string(),
-            BulkString::new(b"world".to_vec()).into(),
-        );
-        map.insert("foo".to_string(), BulkString::new(b"bar".to_vec()).into());
+        map.insert("hello", BulkString::new(b"world".to_vec()));
+        map.insert("foo", BulkString::new(b"bar".to_vec()));
         assert_eq!(frame, map);
 
         Ok(())
     }
+
+    #[test]
+    fn test_map_any_key_roundtrip() -> Result<()> {
+        let mut map = RespMap::new();
+        map.insert(b"name", "redis");
+        map.insert(7, 1.5);
+        map.insert(RespArray::new([1.into(), 2.into()]), RespNull);
+
+        let encoded = RespFrame::from(map.clone()).encode();
+        assert_eq!(
+            encoded,
+            b"%3\r\n$4\r\nname\r\n+redis\r\n:7\r\n,1.5\r\n*2\r\n:1\r\n:2\r\n_\r\n"
+        );
+        let mut buf = BytesMut::from(&encoded[..]);
+        let decoded = RespMap::decode(&mut buf)?;
+        assert_eq!(decoded, map);
+        assert_eq!(decoded.get(&RespFrame::from(7)), Some(&1.5.into()));
+        assert_eq!(decoded["name"], RespFrame::from("redis"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_map_insert_keeps_position() {
+        let mut map = RespMap::new();
+        map.insert("b", 1);
+        map.insert("a", 2);
+        assert_eq!(map.insert("b", 3), Some(1.into()));
+        assert_eq!(
+            map.keys().cloned().collect::<Vec<_>>(),
+            vec!["b".into(), "a".into()]
+        );
+        assert_eq!(map["b"], 3.into());
+
+        assert_eq!(map.remove("b"), Some(3.into()));
+        assert!(!map.contains_key("b"));
+        assert_eq!(map.len(), 1);
+    }
 }
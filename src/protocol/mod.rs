@@ -1,8 +1,20 @@
 mod array;
+mod attribute;
+mod big_number;
 mod bool;
+mod bulk_error;
 mod bulk_strings;
+#[cfg(test)]
+mod conformance;
+#[cfg(feature = "serde")]
+mod de;
+mod decoder;
+mod display;
 mod double;
+mod inline;
 mod integer;
+#[cfg(feature = "json")]
+mod json;
 mod protocols;
 
 mod set;
@@ -10,28 +22,81 @@ mod simple_error;
 
 mod map;
 mod null;
+mod push;
+#[cfg(feature = "serde")]
+mod ser;
 
 mod simple_string;
+mod verbatim_string;
 
-use bytes::{Buf, BytesMut};
-use enum_dispatch::enum_dispatch;
+use bytes::{BufMut, BytesMut};
 use thiserror::Error;
 
+pub(crate) use self::inline::{decode_inline, is_resp_prefix};
 pub use self::{
-    array::RespArray, bulk_strings::BulkString, map::RespMap, null::RespNull, protocols::RespFrame,
-    set::RespSet, simple_error::SimpleError, simple_string::SimpleString,
+    array::RespArray,
+    attribute::RespAttribute,
+    big_number::RespBigNumber,
+    bulk_error::BulkError,
+    bulk_strings::BulkString,
+    decoder::{RespDecoder, RespLimits},
+    map::{MapKey, RespMap},
+    null::RespNull,
+    protocols::RespFrame,
+    push::RespPush,
+    set::RespSet,
+    simple_error::SimpleError,
+    simple_string::SimpleString,
+    verbatim_string::VerbatimString,
+};
+
+#[cfg(feature = "serde")]
+pub use self::{
+    de::{from_bytes, from_frame},
+    ser::{to_bytes, to_frame},
 };
 
 const CRLF: &[u8] = b"\r\n";
 const CRLF_LEN: usize = CRLF.len();
 
-#[enum_dispatch]
+/// protocol version of a connection, RESP3 only types are downgraded for RESP2 clients
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RespVersion {
+    #[default]
+    Resp2,
+    Resp3,
+}
+
 pub trait RespEncode {
-    /// encode the response to a byte array
-    fn encode(self) -> Vec<u8>;
+    /// write the encoded frame to the end of `buf`
+    fn encode_to<B: BufMut>(&self, buf: &mut B);
+
+    /// encode the frame into a new byte array
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.encode_to(&mut buf);
+        buf
+    }
+}
+
+/// write `<prefix><n>\r\n`, used for integers and the lengths of bulk strings and aggregates
+fn put_prefixed_int<B: BufMut>(buf: &mut B, prefix: u8, n: impl itoa::Integer) {
+    buf.put_u8(prefix);
+    buf.put_slice(itoa::Buffer::new().format(n).as_bytes());
+    buf.put_slice(CRLF);
 }
 
-#[derive(Error, Debug, PartialEq, Eq)]
+/// a `fmt::Write` over a `BufMut`, to format values without an intermediate `String`
+struct BufWriter<'a, B>(&'a mut B);
+
+impl<B: BufMut> std::fmt::Write for BufWriter<'_, B> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.0.put_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum RespError {
     #[error("Invalid frame: {0}")]
     InvalidFrame(String),
@@ -39,6 +104,11 @@ pub enum RespError {
     InvalidFrameType(String),
     #[error("Frame is not complete")]
     NotComplete,
+    #[error("{0}")]
+    InvalidInline(String),
+    /// the frame is over one of the `RespLimits`
+    #[error("{0}")]
+    LimitExceeded(String),
     //
     #[error("Parse error: {0}")]
     ParseIntError(#[from] std::num::ParseIntError),
@@ -48,6 +118,35 @@ pub enum RespError {
     ParseFloatError(#[from] std::num::ParseFloatError),
 }
 
+/// failure to convert between a frame and a serde value
+#[cfg(feature = "serde")]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SerdeError {
+    #[error("{0}")]
+    Message(String),
+    /// the frame is an error reply
+    #[error("{0}")]
+    ErrorReply(String),
+    #[error("{0} bytes after the frame")]
+    TrailingBytes(usize),
+    #[error(transparent)]
+    Resp(#[from] RespError),
+}
+
+#[cfg(feature = "serde")]
+impl serde::ser::Error for SerdeError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        SerdeError::Message(msg.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::de::Error for SerdeError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        SerdeError::Message(msg.to_string())
+    }
+}
+
 /// decode the response from a byte array
 pub trait RespDecode: Sized {
     /// redis protocol prefix
@@ -69,119 +168,26 @@ pub trait RespDecode: Sized {
                 ))
             })
     }
-
-    /// expect the length of the response
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError>;
-}
-
-/// find the nth CRLF in the buffer
-fn find_crlf(buf: &[u8], nth: usize) -> Option<usize> {
-    let mut count = 0;
-    for i in 1..buf.len() - 1 {
-        if buf[i] == b'\r' && buf[i + 1] == b'\n' {
-            count += 1;
-            if count == nth {
-                return Some(i);
-            }
-        }
-    }
-    None
-}
-
-/// parse the length of the response from the buffer (end,length)
-fn parse_length(buf: &[u8], prefix: &str) -> Result<(usize, usize), RespError> {
-    let end = extract_simple_frame_data(buf, prefix)?;
-    let s = String::from_utf8_lossy(&buf[prefix.len()..end]);
-    Ok((
-        end,
-        s.parse()
-            .map_err(|_| RespError::InvalidFrame(format!("Invalid length: {:?}", s)))?,
-    ))
-}
-
-fn extract_simple_frame_data(buf: &[u8], prefix: &str) -> Result<usize, RespError> {
-    if buf.len() < 3 {
-        return Err(RespError::NotComplete);
-    }
-
-    if !buf.starts_with(prefix.as_bytes()) {
-        return Err(RespError::InvalidFrameType(format!(
-            "expect: SimpleString({}), got: {:?}",
-            prefix, buf
-        )));
-    }
-
-    let end = find_crlf(buf, 1).ok_or(RespError::NotComplete)?;
-
-    Ok(end)
-}
-
-fn extract_fixed_data(
-    buf: &mut BytesMut,
-    expect: &str,
-    expect_type: &str,
-) -> Result<(), RespError> {
-    if buf.len() < expect.len() {
-        return Err(RespError::NotComplete);
-    }
-
-    if !buf.starts_with(expect.as_bytes()) {
-        return Err(RespError::InvalidFrameType(format!(
-            "expect: {}, got: {:?}",
-            expect_type, buf
-        )));
-    }
-
-    buf.advance(expect.len());
-    Ok(())
 }
 
-fn extract_fixed_data_noadv(
+/// decode a whole frame of one type with a fresh `RespDecoder`, the buffer is left untouched if
+/// the frame is not complete
+fn decode_frame_as<T>(
     buf: &mut BytesMut,
-    expect: &str,
-    expect_type: &str,
-) -> Result<(), RespError> {
-    if buf.len() < expect.len() {
+    prefix: &str,
+    extract: impl FnOnce(RespFrame) -> Option<T>,
+) -> Result<T, RespError> {
+    if buf.is_empty() {
         return Err(RespError::NotComplete);
     }
-
-    if !buf.starts_with(expect.as_bytes()) {
+    if !buf.starts_with(prefix.as_bytes()) {
         return Err(RespError::InvalidFrameType(format!(
             "expect: {}, got: {:?}",
-            expect_type, buf
+            prefix, buf
         )));
     }
-
-    Ok(())
-}
-
-fn calc_total_length(buf: &[u8], end: usize, len: usize, prefix: &str) -> Result<usize, RespError> {
-    let mut total = end + CRLF_LEN;
-    let mut data = &buf[total..];
-    match prefix {
-        "*" | "~" => {
-            // find nth CRLF in the buffer, for array and set, we need to find 1 CRLF for each element
-            for _ in 0..len {
-                let len = RespFrame::expect_length(data)?;
-                data = &data[len..];
-                total += len;
-            }
-            Ok(total)
-        }
-        "%" => {
-            // find nth CRLF in the buffer. For map, we need to find 2 CRLF for each key-value pair
-            for _ in 0..len {
-                let len = SimpleString::expect_length(data)?;
-
-                data = &data[len..];
-                total += len;
-
-                let len = RespFrame::expect_length(data)?;
-                data = &data[len..];
-                total += len;
-            }
-            Ok(total)
-        }
-        _ => Ok(len + CRLF_LEN),
-    }
+    let frame = RespDecoder::new()
+        .decode(buf)?
+        .ok_or(RespError::NotComplete)?;
+    extract(frame).ok_or_else(|| RespError::InvalidFrameType(format!("expect: {}", prefix)))
 }
@@ -1,14 +1,14 @@
-use bytes::BytesMut;
+use bytes::{BufMut, BytesMut};
 
-use super::{RespDecode, RespEncode, RespError};
+use super::{RespDecode, RespEncode, RespError, RespFrame};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
 pub struct RespNull;
 
 // - null: "_\r\n"
 impl RespEncode for RespNull {
-    fn encode(self) -> Vec<u8> {
-        b"_\r\n".to_vec()
+    fn encode_to<B: BufMut>(&self, buf: &mut B) {
+        buf.put_slice(b"_\r\n");
     }
 }
 
@@ -17,12 +17,10 @@ impl RespDecode for RespNull {
     const TYPE: &'static str = "null";
 
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        super::extract_fixed_data(buf, "_\r\n", "Null")?;
-        Ok(RespNull)
-    }
-
-    fn expect_length(_buf: &[u8]) -> Result<usize, RespError> {
-        Ok(3)
+        super::decode_frame_as(buf, Self::PREFIX, |frame| match frame {
+            RespFrame::Null(n) => Some(n),
+            _ => None,
+        })
     }
 }
 
@@ -5,11 +5,12 @@ use crate::protocol::null::RespNull;
 use crate::protocol::set::RespSet;
 use crate::protocol::simple_error::SimpleError;
 use crate::protocol::simple_string::SimpleString;
-use crate::protocol::{RespDecode, RespError};
-use bytes::BytesMut;
-use enum_dispatch::enum_dispatch;
+use crate::protocol::{
+    BulkError, RespAttribute, RespBigNumber, RespDecode, RespDecoder, RespEncode, RespError,
+    RespPush, VerbatimString,
+};
+use bytes::{BufMut, BytesMut};
 
-#[enum_dispatch(RespEncode)]
 #[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum RespFrame {
     SimpleString(SimpleString),
@@ -24,92 +25,150 @@ pub enum RespFrame {
     Double(f64),
     Map(RespMap),
     Set(RespSet),
+    Push(RespPush),
+    BigNumber(RespBigNumber),
+    VerbatimString(VerbatimString),
+    BulkError(BulkError),
+    Attribute(RespAttribute),
 }
+macro_rules! impl_from_variant {
+    ($($variant:ident($ty:ty)),* $(,)?) => {
+        $(
+            impl From<$ty> for RespFrame {
+                fn from(v: $ty) -> Self {
+                    RespFrame::$variant(v)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_variant!(
+    SimpleString(SimpleString),
+    Error(SimpleError),
+    Integer(i64),
+    BulkString(BulkString),
+    Array(RespArray),
+    Null(RespNull),
+    Boolean(bool),
+    Double(f64),
+    Map(RespMap),
+    Set(RespSet),
+    Push(RespPush),
+    BigNumber(RespBigNumber),
+    VerbatimString(VerbatimString),
+    BulkError(BulkError),
+    Attribute(RespAttribute),
+);
+
+/// replies common enough to be written as a single pre-encoded slice
+const ENCODED_OK: &[u8] = b"+OK\r\n";
+const ENCODED_NULL: &[u8] = b"_\r\n";
+const ENCODED_NULL_BULK_STRING: &[u8] = b"$-1\r\n";
+const ENCODED_ZERO: &[u8] = b":0\r\n";
+const ENCODED_ONE: &[u8] = b":1\r\n";
+const ENCODED_EMPTY_ARRAY: &[u8] = b"*0\r\n";
+
+impl RespEncode for RespFrame {
+    fn encode_to<B: BufMut>(&self, buf: &mut B) {
+        match self {
+            RespFrame::SimpleString(s) if s.as_ref() == b"OK" => buf.put_slice(ENCODED_OK),
+            RespFrame::SimpleString(s) => s.encode_to(buf),
+            RespFrame::Error(e) => e.encode_to(buf),
+            RespFrame::Integer(0) => buf.put_slice(ENCODED_ZERO),
+            RespFrame::Integer(1) => buf.put_slice(ENCODED_ONE),
+            RespFrame::Integer(i) => i.encode_to(buf),
+            RespFrame::BulkString(BulkString(None)) => buf.put_slice(ENCODED_NULL_BULK_STRING),
+            RespFrame::BulkString(s) => s.encode_to(buf),
+            RespFrame::Array(RespArray(Some(frames))) if frames.is_empty() => {
+                buf.put_slice(ENCODED_EMPTY_ARRAY)
+            }
+            RespFrame::Array(a) => a.encode_to(buf),
+            RespFrame::Null(_) => buf.put_slice(ENCODED_NULL),
+            RespFrame::Boolean(b) => b.encode_to(buf),
+            RespFrame::Double(d) => d.encode_to(buf),
+            RespFrame::Map(m) => m.encode_to(buf),
+            RespFrame::Set(s) => s.encode_to(buf),
+            RespFrame::Push(p) => p.encode_to(buf),
+            RespFrame::BigNumber(n) => n.encode_to(buf),
+            RespFrame::VerbatimString(s) => s.encode_to(buf),
+            RespFrame::BulkError(e) => e.encode_to(buf),
+            RespFrame::Attribute(a) => a.encode_to(buf),
+        }
+    }
+}
+
 impl RespDecode for RespFrame {
     const PREFIX: &'static str = "";
     const TYPE: &'static str = "RespFrame";
 
+    /// decode a whole frame, the buffer is left untouched if it is not complete. Use a
+    /// `RespDecoder` to keep the progress between reads instead.
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let mut iter = buf.iter().peekable();
-        match iter.peek() {
-            Some(b'+') => {
-                let frame = SimpleString::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b'-') => {
-                let frame = SimpleError::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b':') => {
-                let frame = i64::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b'$') => {
-                // try null bulk string first
-                let frame = BulkString::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b'*') => {
-                // try null array first
-                let frame = RespArray::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b'_') => {
-                let frame = RespNull::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b'#') => {
-                let frame = bool::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b',') => {
-                let frame = f64::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b'%') => {
-                let frame = RespMap::decode(buf)?;
-                Ok(frame.into())
-            }
-            Some(b'~') => {
-                let frame = RespSet::decode(buf)?;
-                Ok(frame.into())
-            }
-            None => Err(RespError::NotComplete),
-            _ => Err(RespError::InvalidFrameType(format!(
-                "expect_length: unknown frame type: {:?}",
-                buf
-            ))),
-        }
+        RespDecoder::new()
+            .decode(buf)?
+            .ok_or(RespError::NotComplete)
     }
+}
 
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
-        let mut iter = buf.iter().peekable();
-        match iter.peek() {
-            Some(b'~') => RespSet::expect_length(buf),
-            Some(b'-') => SimpleError::expect_length(buf),
-            Some(b'*') => RespArray::expect_length(buf),
-            Some(b'%') => RespMap::expect_length(buf),
-            Some(b'$') => BulkString::expect_length(buf),
-            Some(b':') => i64::expect_length(buf),
-            Some(b'+') => SimpleString::expect_length(buf),
-            Some(b'#') => bool::expect_length(buf),
-            Some(b',') => f64::expect_length(buf),
-            Some(b'_') => RespNull::expect_length(buf),
-            _ => Err(RespError::NotComplete),
+impl RespFrame {
+    /// convert RESP3 only types to their RESP2 equivalent, recursively
+    pub fn into_resp2(self) -> RespFrame {
+        match self {
+            RespFrame::Array(RespArray(Some(frames))) => RespArray::new(
+                frames
+                    .into_iter()
+                    .map(|f| f.into_resp2())
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            RespFrame::Null(_) => BulkString::new_null().into(),
+            RespFrame::Boolean(b) => (b as i64).into(),
+            RespFrame::Double(d) => BulkString::from(super::double::format_double(d)).into(),
+            RespFrame::Map(map) => RespArray::new(
+                map.0
+                    .into_iter()
+                    .flat_map(|(k, v)| {
+                        // RESP2 clients expect map keys as bulk strings
+                        let k = match k {
+                            RespFrame::SimpleString(s) => BulkString::new(s.0).into(),
+                            k => k.into_resp2(),
+                        };
+                        [k, v.into_resp2()]
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            RespFrame::Set(RespSet(frames)) | RespFrame::Push(RespPush(frames)) => RespArray::new(
+                frames
+                    .into_iter()
+                    .map(|f| f.into_resp2())
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            RespFrame::BigNumber(n) => BulkString::from(n.0).into(),
+            RespFrame::VerbatimString(s) => BulkString::new(s.data).into(),
+            // a simple error can't carry CR or LF
+            RespFrame::BulkError(e) => {
+                SimpleError::new(String::from_utf8_lossy(&e.0).replace(['\r', '\n'], " ")).into()
+            }
+            // RESP2 has no way to send attributes, only the reply is kept
+            RespFrame::Attribute(a) => a.into_frame().into_resp2(),
+            frame => frame,
         }
     }
 }
 
 impl From<&str> for RespFrame {
     fn from(s: &str) -> Self {
-        SimpleString(s.to_string()).into()
+        SimpleString::from(s).into()
     }
 }
 
 impl From<Option<&[u8]>> for RespFrame {
     fn from(value: Option<&[u8]>) -> Self {
         match value {
-            Some(value) => BulkString(Some(value.to_vec())).into(),
+            Some(value) => BulkString::from(value).into(),
             None => BulkString(None).into(),
         }
     }
@@ -117,12 +176,54 @@ impl From<Option<&[u8]>> for RespFrame {
 
 impl From<&[u8]> for RespFrame {
     fn from(s: &[u8]) -> Self {
-        BulkString(Some(s.to_vec())).into()
+        BulkString::from(s).into()
     }
 }
 
 impl<const N: usize> From<&[u8; N]> for RespFrame {
     fn from(s: &[u8; N]) -> Self {
-        BulkString(Some(s.to_vec())).into()
+        BulkString::from(s).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::RespEncode;
+
+    #[test]
+    fn test_into_resp2() {
+        let mut map = RespMap::new();
+        map.insert("flag", true);
+        map.insert("score", 1.5);
+        map.insert(
+            "tags",
+            RespSet::new([RespNull.into(), BulkString::from("a").into()]),
+        );
+
+        let frame = RespFrame::from(map).into_resp2();
+        assert_eq!(
+            frame.encode(),
+            b"*6\r\n$4\r\nflag\r\n:1\r\n$5\r\nscore\r\n$3\r\n1.5\r\n$4\r\ntags\r\n*2\r\n$-1\r\n$1\r\na\r\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_to_shared_buffer() {
+        let mut buf = BytesMut::from("+PONG\r\n");
+        RespFrame::from(RespArray::new([b"a".into(), 10.into()])).encode_to(&mut buf);
+        RespFrame::from(SimpleString::new("OK")).encode_to(&mut buf);
+        assert_eq!(&buf[..], b"+PONG\r\n*2\r\n$1\r\na\r\n:10\r\n+OK\r\n");
+    }
+
+    #[test]
+    fn test_pre_encoded_replies() {
+        // the constants must match what the generic encoders produce
+        assert_eq!(SimpleString::new("OK").encode(), ENCODED_OK);
+        assert_eq!(RespNull.encode(), ENCODED_NULL);
+        assert_eq!(BulkString::new_null().encode(), ENCODED_NULL_BULK_STRING);
+        assert_eq!(0i64.encode(), ENCODED_ZERO);
+        assert_eq!(1i64.encode(), ENCODED_ONE);
+        assert_eq!(RespArray::new([]).encode(), ENCODED_EMPTY_ARRAY);
     }
 }
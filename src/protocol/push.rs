@@ -0,0 +1,74 @@
+use bytes::{BufMut, BytesMut};
+
+use super::{RespDecode, RespEncode, RespError, RespFrame};
+use std::ops::Deref;
+
+/// "><number-of-elements>\r\n<element-1>...<element-n>"
+/// out of band data such as pub/sub messages and invalidations, RESP3 only
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct RespPush(pub(crate) Vec<RespFrame>);
+
+impl RespEncode for RespPush {
+    fn encode_to<B: BufMut>(&self, buf: &mut B) {
+        super::put_prefixed_int(buf, b'>', self.len());
+        for frame in &self.0 {
+            frame.encode_to(buf);
+        }
+    }
+}
+
+impl RespDecode for RespPush {
+    const PREFIX: &'static str = ">";
+    const TYPE: &'static str = "push";
+
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        super::decode_frame_as(buf, Self::PREFIX, |frame| match frame {
+            RespFrame::Push(v) => Some(v),
+            _ => None,
+        })
+    }
+}
+
+impl RespPush {
+    pub fn new(s: impl Into<Vec<RespFrame>>) -> Self {
+        RespPush(s.into())
+    }
+}
+
+impl Deref for RespPush {
+    type Target = Vec<RespFrame>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_push_encode() {
+        let frame: RespFrame =
+            RespPush::new([b"message".into(), b"chan".into(), b"hi".into()]).into();
+        assert_eq!(
+            frame.encode(),
+            b">3\r\n$7\r\nmessage\r\n$4\r\nchan\r\n$2\r\nhi\r\n"
+        );
+    }
+
+    #[test]
+    fn test_push_decode() -> Result<()> {
+        let mut buf = BytesMut::from(&b">2\r\n$10\r\ninvalidate\r\n*1\r\n$3\r\nkey\r\n"[..]);
+        let frame = RespPush::decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            RespPush::new([
+                b"invalidate".into(),
+                crate::RespArray::new([b"key".into()]).into()
+            ])
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,451 @@
+use serde::ser::{self, Serialize};
+
+use super::SerdeError;
+use super::{BulkString, RespArray, RespBigNumber, RespEncode, RespFrame, RespMap, RespNull};
+
+/// convert a value into a frame: structs and maps become maps, sequences and tuples become
+/// arrays, `None` and `()` become null, strings and bytes become bulk strings. Enum variants
+/// with data become a map of one entry keyed by the variant name.
+pub fn to_frame<T: Serialize + ?Sized>(value: &T) -> Result<RespFrame, SerdeError> {
+    value.serialize(FrameSerializer)
+}
+
+/// convert a value into its RESP encoding, see `to_frame`
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, SerdeError> {
+    Ok(to_frame(value)?.encode())
+}
+
+struct FrameSerializer;
+
+/// `{variant: value}`, how data carrying enum variants are represented
+fn variant_frame(variant: &'static str, value: RespFrame) -> RespFrame {
+    let mut map = RespMap::with_capacity(1);
+    map.push(BulkString::from(variant), value);
+    map.into()
+}
+
+/// integers out of the i64 range are sent as big numbers
+fn wide_integer(n: impl Into<i128> + ToString + Copy) -> RespFrame {
+    match i64::try_from(n.into()) {
+        Ok(n) => n.into(),
+        Err(_) => RespBigNumber(n.to_string()).into(),
+    }
+}
+
+impl ser::Serializer for FrameSerializer {
+    type Ok = RespFrame;
+    type Error = SerdeError;
+
+    type SerializeSeq = SerializeVec;
+    type SerializeTuple = SerializeVec;
+    type SerializeTupleStruct = SerializeVec;
+    type SerializeTupleVariant = SerializeVec;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeMap;
+
+    fn serialize_bool(self, v: bool) -> Result<RespFrame, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<RespFrame, SerdeError> {
+        Ok((v as i64).into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<RespFrame, SerdeError> {
+        Ok((v as i64).into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<RespFrame, SerdeError> {
+        Ok((v as i64).into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<RespFrame, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<RespFrame, SerdeError> {
+        Ok(wide_integer(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<RespFrame, SerdeError> {
+        Ok((v as i64).into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<RespFrame, SerdeError> {
+        Ok((v as i64).into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<RespFrame, SerdeError> {
+        Ok((v as i64).into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<RespFrame, SerdeError> {
+        Ok(wide_integer(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<RespFrame, SerdeError> {
+        Ok(match i64::try_from(v) {
+            Ok(n) => n.into(),
+            Err(_) => RespBigNumber(v.to_string()).into(),
+        })
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<RespFrame, SerdeError> {
+        Ok((v as f64).into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<RespFrame, SerdeError> {
+        Ok(v.into())
+    }
+
+    fn serialize_char(self, v: char) -> Result<RespFrame, SerdeError> {
+        Ok(BulkString::from(v.to_string()).into())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<RespFrame, SerdeError> {
+        Ok(BulkString::from(v).into())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<RespFrame, SerdeError> {
+        Ok(BulkString::from(v).into())
+    }
+
+    fn serialize_none(self) -> Result<RespFrame, SerdeError> {
+        Ok(RespNull.into())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<RespFrame, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<RespFrame, SerdeError> {
+        Ok(RespNull.into())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<RespFrame, SerdeError> {
+        Ok(RespNull.into())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<RespFrame, SerdeError> {
+        Ok(BulkString::from(variant).into())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<RespFrame, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<RespFrame, SerdeError> {
+        Ok(variant_frame(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeVec, SerdeError> {
+        Ok(SerializeVec::new(len.unwrap_or_default(), None))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeVec, SerdeError> {
+        Ok(SerializeVec::new(len, None))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeVec, SerdeError> {
+        Ok(SerializeVec::new(len, None))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVec, SerdeError> {
+        Ok(SerializeVec::new(len, Some(variant)))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap, SerdeError> {
+        Ok(SerializeMap::new(len.unwrap_or_default(), None))
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeMap, SerdeError> {
+        Ok(SerializeMap::new(len, None))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeMap, SerdeError> {
+        Ok(SerializeMap::new(len, Some(variant)))
+    }
+}
+
+/// the elements of an array, wrapped in a map if they belong to an enum variant
+struct SerializeVec {
+    items: Vec<RespFrame>,
+    variant: Option<&'static str>,
+}
+
+impl SerializeVec {
+    fn new(len: usize, variant: Option<&'static str>) -> Self {
+        // the length comes from the value being serialized, it can be trusted
+        Self {
+            items: Vec::with_capacity(len),
+            variant,
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.items.push(value.serialize(FrameSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<RespFrame, SerdeError> {
+        let frame = RespArray::new(self.items).into();
+        Ok(match self.variant {
+            Some(variant) => variant_frame(variant, frame),
+            None => frame,
+        })
+    }
+}
+
+impl ser::SerializeSeq for SerializeVec {
+    type Ok = RespFrame;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RespFrame, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeVec {
+    type Ok = RespFrame;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RespFrame, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeVec {
+    type Ok = RespFrame;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RespFrame, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeVec {
+    type Ok = RespFrame;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<RespFrame, SerdeError> {
+        self.finish()
+    }
+}
+
+/// the entries of a map, struct fields are keyed by their name as bulk strings
+struct SerializeMap {
+    map: RespMap,
+    next_key: Option<RespFrame>,
+    variant: Option<&'static str>,
+}
+
+impl SerializeMap {
+    fn new(len: usize, variant: Option<&'static str>) -> Self {
+        Self {
+            map: RespMap::with_capacity(len),
+            next_key: None,
+            variant,
+        }
+    }
+
+    fn finish(self) -> Result<RespFrame, SerdeError> {
+        let frame = self.map.into();
+        Ok(match self.variant {
+            Some(variant) => variant_frame(variant, frame),
+            None => frame,
+        })
+    }
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = RespFrame;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        self.next_key = Some(key.serialize(FrameSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| SerdeError::Message("map value without a key".to_string()))?;
+        // serde maps can't hold the same key twice, there is nothing to look up
+        self.map.push(key, value.serialize(FrameSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<RespFrame, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = RespFrame;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.map
+            .push(BulkString::from(key), value.serialize(FrameSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<RespFrame, SerdeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeMap {
+    type Ok = RespFrame;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<RespFrame, SerdeError> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct User {
+        name: String,
+        age: u32,
+        email: Option<String>,
+        tags: Vec<&'static str>,
+    }
+
+    #[derive(Serialize)]
+    enum Event {
+        Ping,
+        Move { x: i64 },
+        Say(String),
+    }
+
+    #[test]
+    fn test_struct_to_frame() -> Result<()> {
+        let user = User {
+            name: "alice".to_string(),
+            age: 30,
+            email: None,
+            tags: vec!["a", "b"],
+        };
+        let mut expected = RespMap::new();
+        expected.insert(b"name", b"alice");
+        expected.insert(b"age", 30);
+        expected.insert(b"email", RespNull);
+        expected.insert(b"tags", RespArray::new([b"a".into(), b"b".into()]));
+        assert_eq!(to_frame(&user)?, expected.into());
+
+        assert_eq!(
+            to_bytes(&user)?,
+            b"%4\r\n$4\r\nname\r\n$5\r\nalice\r\n$3\r\nage\r\n:30\r\n$5\r\nemail\r\n_\r\n\
+              $4\r\ntags\r\n*2\r\n$1\r\na\r\n$1\r\nb\r\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_values_to_frame() -> Result<()> {
+        assert_eq!(to_frame(&true)?, true.into());
+        assert_eq!(to_frame(&1.5)?, 1.5.into());
+        assert_eq!(to_frame(&())?, RespNull.into());
+        assert_eq!(to_frame(&Some(3u8))?, 3.into());
+        assert_eq!(
+            to_frame(&u64::MAX)?,
+            RespBigNumber::new("18446744073709551615")?.into()
+        );
+        assert_eq!(
+            to_frame(&(1, "a"))?,
+            RespArray::new([1.into(), b"a".into()]).into()
+        );
+
+        let map = BTreeMap::from([(1, "one"), (2, "two")]);
+        let mut expected = RespMap::new();
+        expected.insert(1, b"one");
+        expected.insert(2, b"two");
+        assert_eq!(to_frame(&map)?, expected.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_enum_to_frame() -> Result<()> {
+        assert_eq!(to_frame(&Event::Ping)?, b"Ping".into());
+
+        let mut fields = RespMap::new();
+        fields.insert(b"x", 1);
+        let mut expected = RespMap::new();
+        expected.insert(b"Move", fields);
+        assert_eq!(to_frame(&Event::Move { x: 1 })?, expected.into());
+
+        let mut expected = RespMap::new();
+        expected.insert(b"Say", b"hi");
+        assert_eq!(to_frame(&Event::Say("hi".to_string()))?, expected.into());
+        Ok(())
+    }
+}
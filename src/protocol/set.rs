@@ -1,8 +1,6 @@
-use bytes::{Buf, BytesMut};
+use bytes::{BufMut, BytesMut};
 
-use super::{
-    calc_total_length, parse_length, RespDecode, RespEncode, RespError, RespFrame, CRLF_LEN,
-};
+use super::{RespDecode, RespEncode, RespError, RespFrame};
 use std::ops::Deref;
 
 /// "~<number-of-elements>\r\n<element-1>...<element-n>"
@@ -10,13 +8,11 @@ use std::ops::Deref;
 pub struct RespSet(pub(crate) Vec<RespFrame>);
 
 impl RespEncode for RespSet {
-    fn encode(self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(2048);
-        buf.extend_from_slice(&format!("~{}\r\n", self.len()).into_bytes());
-        for frame in self.0 {
-            buf.extend_from_slice(&frame.encode());
+    fn encode_to<B: BufMut>(&self, buf: &mut B) {
+        super::put_prefixed_int(buf, b'~', self.len());
+        for frame in &self.0 {
+            frame.encode_to(buf);
         }
-        buf
     }
 }
 
@@ -25,27 +21,10 @@ impl RespDecode for RespSet {
     const TYPE: &'static str = "set";
 
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?;
-
-        let total_len = calc_total_length(buf, end, len, Self::PREFIX)?;
-
-        if buf.len() < total_len {
-            return Err(RespError::NotComplete);
-        }
-
-        buf.advance(end + CRLF_LEN);
-
-        let mut frames = Vec::new();
-        for _ in 0..len {
-            frames.push(RespFrame::decode(buf)?);
-        }
-
-        Ok(RespSet::new(frames))
-    }
-
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
-        let (end, len) = parse_length(buf, Self::PREFIX)?;
-        calc_total_length(buf, end, len, Self::PREFIX)
+        super::decode_frame_as(buf, Self::PREFIX, |frame| match frame {
+            RespFrame::Set(v) => Some(v),
+            _ => None,
+        })
     }
 }
 
@@ -80,7 +59,7 @@ mod tests {
         .into();
         assert_eq!(
             frame.encode(),
-            b"~2\r\n*2\r\n:+1234\r\n#t\r\n$5\r\nworld\r\n"
+            b"~2\r\n*2\r\n:1234\r\n#t\r\n$5\r\nworld\r\n"
         );
     }
 
@@ -1,16 +1,20 @@
 use std::ops::Deref;
 
-use bytes::BytesMut;
+use bytes::{BufMut, BytesMut};
 
-use super::{extract_simple_frame_data, RespDecode, RespEncode, RespError, CRLF_LEN};
+use super::{RespDecode, RespEncode, RespError, RespFrame};
+
+const PROTOCOL_ERROR: &str = "ERR Protocol error:";
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
 pub struct SimpleError(pub(crate) String);
 
 // - error: "-Error message\r\n"
 impl RespEncode for SimpleError {
-    fn encode(self) -> Vec<u8> {
-        format!("-{}\r\n", self.0).into_bytes()
+    fn encode_to<B: BufMut>(&self, buf: &mut B) {
+        buf.put_u8(b'-');
+        buf.put_slice(self.0.as_bytes());
+        buf.put_slice(super::CRLF);
     }
 }
 
@@ -19,16 +23,10 @@ impl RespDecode for SimpleError {
     const TYPE: &'static str = "simple_error";
 
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let end = extract_simple_frame_data(buf, Self::PREFIX)?;
-        // split the buffer
-        let data = buf.split_to(end + CRLF_LEN);
-        let s = String::from_utf8_lossy(&data[Self::PREFIX.len()..end]);
-        Ok(SimpleError::new(s.to_string()))
-    }
-
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
-        let end = extract_simple_frame_data(buf, Self::PREFIX)?;
-        Ok(end + CRLF_LEN)
+        super::decode_frame_as(buf, Self::PREFIX, |frame| match frame {
+            RespFrame::Error(e) => Some(e),
+            _ => None,
+        })
     }
 }
 
@@ -44,6 +42,13 @@ impl From<&str> for SimpleError {
     }
 }
 
+impl From<RespError> for SimpleError {
+    fn from(e: RespError) -> Self {
+        let msg = format!("{} {}", PROTOCOL_ERROR, e).replace(['\r', '\n'], " ");
+        SimpleError(msg)
+    }
+}
+
 impl Deref for SimpleError {
     type Target = String;
 
@@ -76,4 +81,13 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_protocol_error() {
+        let frame: RespFrame = SimpleError::from(RespError::InvalidFrame("bad\r\n".into())).into();
+        assert_eq!(
+            frame.encode(),
+            b"-ERR Protocol error: Invalid frame: bad  \r\n"
+        );
+    }
 }
@@ -1,24 +1,25 @@
 use std::ops::Deref;
 
-use bytes::BytesMut;
+use bytes::{BufMut, Bytes, BytesMut};
 
-use super::{RespDecode, RespEncode, RespError};
-
-use super::{extract_simple_frame_data, CRLF_LEN};
+use super::{RespDecode, RespEncode, RespError, RespFrame, CRLF};
 
+/// raw bytes without CR or LF, shared with the read buffer like `BulkString`
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
-pub struct SimpleString(pub(crate) String);
+pub struct SimpleString(pub(crate) Bytes);
 
 impl SimpleString {
-    pub fn new(s: impl Into<String>) -> Self {
+    pub fn new(s: impl Into<Bytes>) -> Self {
         SimpleString(s.into())
     }
 }
 
 // - simple string: "+OK\r\n"
 impl RespEncode for SimpleString {
-    fn encode(self) -> Vec<u8> {
-        format!("+{}\r\n", self.0).into_bytes()
+    fn encode_to<B: BufMut>(&self, buf: &mut B) {
+        buf.put_u8(b'+');
+        buf.put_slice(&self.0);
+        buf.put_slice(CRLF);
     }
 }
 
@@ -27,33 +28,27 @@ impl RespDecode for SimpleString {
     const TYPE: &'static str = "simple_String";
 
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let end = extract_simple_frame_data(buf, Self::PREFIX)?;
-        // split the buffer
-        let data = buf.split_to(end + CRLF_LEN);
-        let s = String::from_utf8_lossy(&data[Self::PREFIX.len()..end]);
-        Ok(SimpleString::new(s.to_string()))
-    }
-
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
-        let end = extract_simple_frame_data(buf, Self::PREFIX)?;
-        Ok(end + CRLF_LEN)
+        super::decode_frame_as(buf, Self::PREFIX, |frame| match frame {
+            RespFrame::SimpleString(s) => Some(s),
+            _ => None,
+        })
     }
 }
 
 impl From<&str> for SimpleString {
     fn from(s: &str) -> Self {
-        SimpleString(s.to_string())
+        SimpleString(Bytes::copy_from_slice(s.as_bytes()))
     }
 }
 
-impl AsRef<str> for SimpleString {
-    fn as_ref(&self) -> &str {
+impl AsRef<[u8]> for SimpleString {
+    fn as_ref(&self) -> &[u8] {
         &self.0
     }
 }
 
 impl Deref for SimpleString {
-    type Target = String;
+    type Target = Bytes;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -0,0 +1,78 @@
+use bytes::{BufMut, Bytes, BytesMut};
+
+use super::{RespDecode, RespEncode, RespError, RespFrame, CRLF};
+
+/// =<length>\r\n<format>:<data>\r\n
+/// a string with a three letters format, `txt` or `mkd`. The length counts the format and `:`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+pub struct VerbatimString {
+    pub(crate) format: [u8; 3],
+    pub(crate) data: Bytes,
+}
+
+impl RespEncode for VerbatimString {
+    fn encode_to<B: BufMut>(&self, buf: &mut B) {
+        super::put_prefixed_int(buf, b'=', self.data.len() + 4);
+        buf.put_slice(&self.format);
+        buf.put_u8(b':');
+        buf.put_slice(&self.data);
+        buf.put_slice(CRLF);
+    }
+}
+
+impl RespDecode for VerbatimString {
+    const PREFIX: &'static str = "=";
+    const TYPE: &'static str = "verbatim_string";
+
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        super::decode_frame_as(buf, Self::PREFIX, |frame| match frame {
+            RespFrame::VerbatimString(v) => Some(v),
+            _ => None,
+        })
+    }
+}
+
+impl VerbatimString {
+    pub fn new(format: [u8; 3], data: impl Into<Bytes>) -> Self {
+        VerbatimString {
+            format,
+            data: data.into(),
+        }
+    }
+
+    /// a plain text verbatim string
+    pub fn txt(data: impl Into<Bytes>) -> Self {
+        Self::new(*b"txt", data)
+    }
+
+    pub fn format(&self) -> &[u8; 3] {
+        &self.format
+    }
+
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_verbatim_string() -> Result<()> {
+        let s = VerbatimString::txt("Some string");
+        let encoded = RespFrame::from(s.clone()).encode();
+        assert_eq!(encoded, b"=15\r\ntxt:Some string\r\n");
+
+        let mut buf = BytesMut::from(&encoded[..]);
+        assert_eq!(VerbatimString::decode(&mut buf)?, s);
+        Ok(())
+    }
+
+    #[test]
+    fn test_verbatim_string_invalid() {
+        let mut buf = BytesMut::from("=3\r\ntxt\r\n");
+        assert!(VerbatimString::decode(&mut buf).is_err());
+    }
+}
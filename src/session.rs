@@ -0,0 +1,242 @@
+use crate::RespVersion;
+use std::{
+    fmt::Write,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    time::Instant,
+};
+use tokio_util::sync::CancellationToken;
+
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// per connection state, commands receive it next to the shared backend
+#[derive(Debug)]
+pub struct Session {
+    pub(crate) client: Arc<Client>,
+    /// the reply of the current command isn't sent
+    skip_reply: bool,
+}
+
+/// a connected client, shared between its connection and the server wide registry in `Backend`
+#[derive(Debug)]
+pub struct Client {
+    id: u64,
+    addr: String,
+    laddr: String,
+    /// connected through the unix socket rather than TCP
+    unix_socket: bool,
+    created: Instant,
+    state: Mutex<ClientState>,
+    killed: CancellationToken,
+}
+
+/// the mutable part of a client, reported by CLIENT LIST and CLIENT INFO
+#[derive(Debug)]
+pub(crate) struct ClientState {
+    pub(crate) name: Option<String>,
+    pub(crate) protocol: RespVersion,
+    pub(crate) db: usize,
+    pub(crate) last_cmd: Option<String>,
+    pub(crate) last_interaction: Instant,
+    pub(crate) lib_name: Option<String>,
+    pub(crate) lib_ver: Option<String>,
+    /// bytes read but not parsed yet, and the free space of the read buffer
+    pub(crate) qbuf: usize,
+    pub(crate) qbuf_free: usize,
+    /// reply bytes not written to the socket yet
+    pub(crate) omem: usize,
+}
+
+impl Session {
+    /// a session that is not attached to a connection
+    pub fn new() -> Self {
+        Self::with_client(Client::new("", ""))
+    }
+
+    pub(crate) fn with_client(client: Client) -> Self {
+        Self {
+            client: Arc::new(client),
+            skip_reply: false,
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.client.id
+    }
+
+    /// the protocol replies are encoded with, set by HELLO
+    pub fn protocol(&self) -> RespVersion {
+        self.client.state().protocol
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.client.state().name.clone()
+    }
+
+    pub fn client(&self) -> &Arc<Client> {
+        &self.client
+    }
+
+    /// drop the reply of the command being executed
+    pub fn skip_reply(&mut self) {
+        self.skip_reply = true;
+    }
+
+    /// whether the reply of the last command is dropped, resetting it for the next one
+    pub(crate) fn take_skip_reply(&mut self) -> bool {
+        std::mem::take(&mut self.skip_reply)
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Client {
+    pub fn new(addr: impl Into<String>, laddr: impl Into<String>) -> Self {
+        Self::with_addr(addr.into(), laddr.into(), false)
+    }
+
+    /// a client of the unix socket at `path`, redis reports its address as `path:0`
+    pub fn new_unix(path: impl AsRef<Path>) -> Self {
+        let addr = format!("{}:0", path.as_ref().display());
+        Self::with_addr(addr.clone(), addr, true)
+    }
+
+    fn with_addr(addr: String, laddr: String, unix_socket: bool) -> Self {
+        let now = Instant::now();
+        Self {
+            id: NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed),
+            addr,
+            laddr,
+            unix_socket,
+            created: now,
+            state: Mutex::new(ClientState {
+                name: None,
+                protocol: RespVersion::default(),
+                db: 0,
+                last_cmd: None,
+                last_interaction: now,
+                lib_name: None,
+                lib_ver: None,
+                qbuf: 0,
+                qbuf_free: 0,
+                omem: 0,
+            }),
+            killed: CancellationToken::new(),
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    pub fn laddr(&self) -> &str {
+        &self.laddr
+    }
+
+    pub fn is_unix_socket(&self) -> bool {
+        self.unix_socket
+    }
+
+    pub fn age(&self) -> u64 {
+        self.created.elapsed().as_secs()
+    }
+
+    pub(crate) fn state(&self) -> MutexGuard<'_, ClientState> {
+        // the state holds plain values, a panic while holding the lock can't break it
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// ask the connection to close, it finishes the current batch first
+    pub fn kill(&self) {
+        self.killed.cancel();
+    }
+
+    pub fn is_killed(&self) -> bool {
+        self.killed.is_cancelled()
+    }
+
+    /// resolves once the client is killed
+    pub async fn killed(&self) {
+        self.killed.cancelled().await
+    }
+
+    /// the line CLIENT LIST and CLIENT INFO print for this client
+    pub fn info(&self) -> String {
+        let state = self.state();
+        let resp = match state.protocol {
+            RespVersion::Resp2 => 2,
+            RespVersion::Resp3 => 3,
+        };
+        let flags = if self.unix_socket { "U" } else { "N" };
+        let mut info = String::new();
+        let _ = write!(
+            info,
+            "id={} addr={} laddr={} name={} age={} idle={} flags={} db={} sub=0 psub=0 ssub=0 \
+             multi=-1 qbuf={} qbuf-free={} obl=0 oll=0 omem={} cmd={} user=default resp={} \
+             lib-name={} lib-ver={}",
+            self.id,
+            self.addr,
+            self.laddr,
+            state.name.as_deref().unwrap_or_default(),
+            self.age(),
+            state.last_interaction.elapsed().as_secs(),
+            flags,
+            state.db,
+            state.qbuf,
+            state.qbuf_free,
+            state.omem,
+            state.last_cmd.as_deref().unwrap_or("NULL"),
+            resp,
+            state.lib_name.as_deref().unwrap_or_default(),
+            state.lib_ver.as_deref().unwrap_or_default(),
+        );
+        info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_info() {
+        let client = Client::new("127.0.0.1:50000", "127.0.0.1:6379");
+        client.state().name = Some("worker".to_string());
+        client.state().last_cmd = Some("client|info".to_string());
+
+        let info = client.info();
+        assert!(info.starts_with(&format!(
+            "id={} addr=127.0.0.1:50000 laddr=127.0.0.1:6379 name=worker age=0 idle=0 flags=N db=0",
+            client.id()
+        )));
+        assert!(info.contains(" cmd=client|info user=default resp=2 "));
+    }
+
+    #[test]
+    fn test_unix_client_info() {
+        let client = Client::new_unix("/tmp/redis.sock");
+        assert!(client.is_unix_socket());
+        assert!(client.info().contains(
+            " addr=/tmp/redis.sock:0 laddr=/tmp/redis.sock:0 name= age=0 idle=0 flags=U "
+        ));
+    }
+
+    #[test]
+    fn test_client_kill() {
+        let session = Session::new();
+        assert!(!session.client().is_killed());
+        session.client().kill();
+        assert!(session.client().is_killed());
+    }
+}